@@ -0,0 +1,300 @@
+//! Declarations (announces) made with the first trick: sequences and four-of-a-kinds.
+//!
+//! Only the team holding the best declaration scores; its opponents' declarations are ignored.
+
+use std::fmt;
+
+use super::cards;
+use super::pos;
+
+// Ranks in sequence order, from the lowest to the highest.
+const SEQUENCE: [cards::Rank; 8] = [
+    cards::Rank::Rank7,
+    cards::Rank::Rank8,
+    cards::Rank::Rank9,
+    cards::Rank::RankX,
+    cards::Rank::RankJ,
+    cards::Rank::RankQ,
+    cards::Rank::RankK,
+    cards::Rank::RankA,
+];
+
+// Ranks that can form a carré, from the strongest to the weakest.
+const CARRES: [cards::Rank; 6] = [
+    cards::Rank::RankJ,
+    cards::Rank::Rank9,
+    cards::Rank::RankA,
+    cards::Rank::RankX,
+    cards::Rank::RankK,
+    cards::Rank::RankQ,
+];
+
+/// A single declaration.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Announce {
+    /// At least three consecutive cards in the same suit.
+    Sequence {
+        /// Suit of the sequence.
+        suit: cards::Suit,
+        /// Highest card of the sequence.
+        high: cards::Rank,
+        /// Number of cards in the sequence.
+        len: usize,
+    },
+    /// All four cards of the same rank.
+    Carre(cards::Rank),
+}
+
+impl Announce {
+    /// Returns the points this declaration is worth.
+    ///
+    /// * Tierce (3 cards): 20
+    /// * Quarte (4 cards): 50
+    /// * Quinte (5 cards or more): 100
+    /// * Carré: 200 for jacks, 150 for nines, 100 otherwise
+    pub fn value(self) -> i32 {
+        match self {
+            Announce::Sequence { len: 3, .. } => 20,
+            Announce::Sequence { len: 4, .. } => 50,
+            Announce::Sequence { .. } => 100,
+            Announce::Carre(cards::Rank::RankJ) => 200,
+            Announce::Carre(cards::Rank::Rank9) => 150,
+            Announce::Carre(_) => 100,
+        }
+    }
+
+    /// Returns the usual name of this declaration.
+    pub fn name(self) -> &'static str {
+        match self {
+            Announce::Sequence { len: 3, .. } => "Tierce",
+            Announce::Sequence { len: 4, .. } => "Quarte",
+            Announce::Sequence { .. } => "Quinte",
+            Announce::Carre(_) => "Carré",
+        }
+    }
+
+    // Key used to compare declarations: value first, then length,
+    // then height, and finally sequences in the trump suit.
+    fn strength(self, trump: cards::Trump) -> (i32, usize, usize, bool) {
+        match self {
+            Announce::Sequence { suit, high, len } => (
+                self.value(),
+                len,
+                sequence_index(high),
+                trump.suit() == Some(suit),
+            ),
+            Announce::Carre(rank) => (
+                self.value(),
+                4,
+                CARRES.len() - CARRES.iter().position(|&r| r == rank).unwrap(),
+                false,
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Announce {
+    /// Writes the declaration name and its highest card (ex: `"Quarte K♥"`, `"Carré J"`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Announce::Sequence { suit, high, .. } => {
+                write!(f, "{} {}{}", self.name(), high, suit)
+            }
+            Announce::Carre(rank) => write!(f, "{} {}", self.name(), rank),
+        }
+    }
+}
+
+fn sequence_index(rank: cards::Rank) -> usize {
+    SEQUENCE.iter().position(|&r| r == rank).unwrap()
+}
+
+/// Returns every declaration found in the given hand.
+///
+/// Carrés come first, then sequences. A card may be part of both.
+pub fn find_announces(hand: cards::Hand) -> Vec<Announce> {
+    let mut result = Vec::new();
+
+    for &rank in CARRES.iter() {
+        if (0..4).all(|n| hand.has(cards::Card::new(cards::Suit::from_n(n), rank))) {
+            result.push(Announce::Carre(rank));
+        }
+    }
+
+    for n in 0..4 {
+        let suit = cards::Suit::from_n(n);
+        let mut len = 0;
+        for (i, &rank) in SEQUENCE.iter().enumerate() {
+            if hand.has(cards::Card::new(suit, rank)) {
+                len += 1;
+            } else {
+                len = 0;
+            }
+
+            // Only keep maximal sequences.
+            let ends =
+                i + 1 == SEQUENCE.len() || !hand.has(cards::Card::new(suit, SEQUENCE[i + 1]));
+            if len >= 3 && ends {
+                result.push(Announce::Sequence {
+                    suit,
+                    high: rank,
+                    len,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the team holding the best declaration, if any.
+///
+/// On a perfect tie, the first player to speak (starting from `first`) wins.
+pub fn best_team(
+    announces: &[Vec<Announce>; 4],
+    first: pos::PlayerPos,
+    trump: cards::Trump,
+) -> Option<pos::Team> {
+    let mut best: Option<(pos::PlayerPos, (i32, usize, usize, bool))> = None;
+    for player in first.until_n(4) {
+        for announce in &announces[player as usize] {
+            let strength = announce.strength(trump);
+            let better = match best {
+                Some((_, b)) => strength > b,
+                None => true,
+            };
+            if better {
+                best = Some((player, strength));
+            }
+        }
+    }
+
+    best.map(|(player, _)| player.team())
+}
+
+/// Returns the declaration points scored by each team.
+///
+/// Only the team with the best declaration scores, for all its declarations.
+pub fn scores(
+    announces: &[Vec<Announce>; 4],
+    first: pos::PlayerPos,
+    trump: cards::Trump,
+) -> [i32; 2] {
+    let mut scores = [0; 2];
+    if let Some(team) = best_team(announces, first, trump) {
+        scores[team as usize] = team
+            .players()
+            .iter()
+            .flat_map(|&p| announces[p as usize].iter())
+            .map(|a| a.value())
+            .sum();
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cards, pos};
+
+    fn hand(cards: &[(cards::Suit, cards::Rank)]) -> cards::Hand {
+        let mut hand = cards::Hand::new();
+        for &(suit, rank) in cards {
+            hand.add(cards::Card::new(suit, rank));
+        }
+        hand
+    }
+
+    #[test]
+    fn test_find_announces() {
+        use cards::Rank::*;
+        use cards::Suit::*;
+
+        let h = hand(&[
+            (Heart, Rank9),
+            (Heart, RankX),
+            (Heart, RankJ),
+            (Heart, RankQ),
+            (Spade, RankJ),
+            (Diamond, RankJ),
+            (Club, RankJ),
+            (Club, Rank7),
+        ]);
+        let announces = find_announces(h);
+        assert_eq!(
+            announces,
+            vec![
+                Announce::Carre(RankJ),
+                Announce::Sequence {
+                    suit: Heart,
+                    high: RankQ,
+                    len: 4
+                },
+            ]
+        );
+        assert_eq!(announces[0].value(), 200);
+        assert_eq!(announces[1].value(), 50);
+        assert_eq!(announces[1].to_string(), "Quarte Q♥");
+
+        // Two cards in a row are not enough.
+        let h = hand(&[(Spade, RankK), (Spade, RankA), (Club, Rank7)]);
+        assert!(find_announces(h).is_empty());
+    }
+
+    #[test]
+    fn test_best_team() {
+        use cards::Rank::*;
+        use cards::Suit::*;
+
+        let tierce = |suit, high| Announce::Sequence { suit, high, len: 3 };
+        let mut announces: [Vec<Announce>; 4] = Default::default();
+        announces[0] = vec![tierce(Heart, RankK), tierce(Club, Rank9)];
+        announces[1] = vec![tierce(Spade, RankA)];
+
+        // Higher tierce wins.
+        assert_eq!(
+            best_team(&announces, pos::PlayerPos::P0, Heart.into()),
+            Some(pos::Team::T13)
+        );
+        assert_eq!(
+            scores(&announces, pos::PlayerPos::P0, Heart.into()),
+            [0, 20]
+        );
+
+        // Same height: the trump sequence wins.
+        announces[1] = vec![tierce(Spade, RankK)];
+        assert_eq!(
+            best_team(&announces, pos::PlayerPos::P0, Spade.into()),
+            Some(pos::Team::T13)
+        );
+        // Perfect tie: first player wins.
+        assert_eq!(
+            best_team(&announces, pos::PlayerPos::P1, cards::Trump::NoTrump),
+            Some(pos::Team::T13)
+        );
+        assert_eq!(
+            scores(&announces, pos::PlayerPos::P0, cards::Trump::NoTrump),
+            [40, 0]
+        );
+
+        // At equal value, a quinte beats a carré.
+        announces[2] = vec![Announce::Carre(RankQ)];
+        announces[3] = vec![Announce::Sequence {
+            suit: Diamond,
+            high: RankA,
+            len: 5,
+        }];
+        assert_eq!(
+            best_team(&announces, pos::PlayerPos::P0, Heart.into()),
+            Some(pos::Team::T13)
+        );
+        assert_eq!(
+            best_team(
+                &[vec![], vec![], vec![], vec![]],
+                pos::PlayerPos::P0,
+                Heart.into()
+            ),
+            None
+        );
+    }
+}
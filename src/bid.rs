@@ -10,7 +10,12 @@ use super::pos;
 /// Goal set by a contract.
 ///
 /// Determines the winning conditions and the score on success.
-#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+///
+/// Targets are ordered as bids: a capot is higher than a 250, and a generale
+/// is the highest of all even if it is worth the same score as a capot.
+#[derive(
+    Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize,
+)]
 pub enum Target {
     /// Team must get 80 points
     Contract80,
@@ -30,12 +35,61 @@ pub enum Target {
     Contract150,
     /// Team must get 160 points
     Contract160,
-    /// Team must win all tricks
+    /// Team must get 170 points
+    Contract170,
+    /// Team must get 180 points
+    Contract180,
+    /// Team must get 190 points
+    Contract190,
+    /// Team must get 200 points
+    Contract200,
+    /// Team must get 210 points
+    Contract210,
+    /// Team must get 220 points
+    Contract220,
+    /// Team must get 230 points
+    Contract230,
+    /// Team must get 240 points
+    Contract240,
+    /// Team must get 250 points
+    Contract250,
+    /// Team must win all tricks (announced capot)
     ContractCapot,
+    /// The author of the contract must win all tricks alone
+    Generale,
 }
 
 impl Target {
+    /// Returns all targets, from the lowest to the highest.
+    pub fn all() -> [Target; 20] {
+        [
+            Target::Contract80,
+            Target::Contract90,
+            Target::Contract100,
+            Target::Contract110,
+            Target::Contract120,
+            Target::Contract130,
+            Target::Contract140,
+            Target::Contract150,
+            Target::Contract160,
+            Target::Contract170,
+            Target::Contract180,
+            Target::Contract190,
+            Target::Contract200,
+            Target::Contract210,
+            Target::Contract220,
+            Target::Contract230,
+            Target::Contract240,
+            Target::Contract250,
+            Target::ContractCapot,
+            Target::Generale,
+        ]
+    }
+
     /// Returns the score this target would give on success.
+    ///
+    /// An announced capot is worth 500, and is lost entirely if a single trick is lost.
+    /// A capot made without announcing it only earns a bonus (see `CAPOT_BONUS`).
     pub fn score(self) -> i32 {
         match self {
             Target::Contract80 => 80,
@@ -47,7 +101,17 @@ impl Target {
             Target::Contract140 => 140,
             Target::Contract150 => 150,
             Target::Contract160 => 160,
-            Target::ContractCapot => 250,
+            Target::Contract170 => 170,
+            Target::Contract180 => 180,
+            Target::Contract190 => 190,
+            Target::Contract200 => 200,
+            Target::Contract210 => 210,
+            Target::Contract220 => 220,
+            Target::Contract230 => 230,
+            Target::Contract240 => 240,
+            Target::Contract250 => 250,
+            Target::ContractCapot => 500,
+            Target::Generale => 500,
         }
     }
 
@@ -62,14 +126,34 @@ impl Target {
             Target::Contract140 => "140",
             Target::Contract150 => "150",
             Target::Contract160 => "160",
+            Target::Contract170 => "170",
+            Target::Contract180 => "180",
+            Target::Contract190 => "190",
+            Target::Contract200 => "200",
+            Target::Contract210 => "210",
+            Target::Contract220 => "220",
+            Target::Contract230 => "230",
+            Target::Contract240 => "240",
+            Target::Contract250 => "250",
             Target::ContractCapot => "Capot",
+            Target::Generale => "Generale",
         }
     }
 
+    /// Returns the next higher target, or `None` for a generale.
+    pub fn next(self) -> Option<Target> {
+        Target::all().get(self as usize + 1).cloned()
+    }
+
     /// Determines whether this target was reached.
-    pub fn victory(self, points: i32, capot: bool) -> bool {
+    ///
+    /// * `points`: points made by the team holding the contract.
+    /// * `capot`: `true` if the team won every trick.
+    /// * `generale`: `true` if the author of the contract won every trick.
+    pub fn victory(self, points: i32, capot: bool, generale: bool) -> bool {
         match self {
             Target::ContractCapot => capot,
+            Target::Generale => generale,
             other => points >= other.score(),
         }
     }
@@ -89,27 +173,40 @@ impl FromStr for Target {
             "140" => Ok(Target::Contract140),
             "150" => Ok(Target::Contract150),
             "160" => Ok(Target::Contract160),
+            "170" => Ok(Target::Contract170),
+            "180" => Ok(Target::Contract180),
+            "190" => Ok(Target::Contract190),
+            "200" => Ok(Target::Contract200),
+            "210" => Ok(Target::Contract210),
+            "220" => Ok(Target::Contract220),
+            "230" => Ok(Target::Contract230),
+            "240" => Ok(Target::Contract240),
+            "250" => Ok(Target::Contract250),
             "Capot" => Ok(Target::ContractCapot),
+            "Generale" | "Générale" => Ok(Target::Generale),
             _ => Err(format!("invalid target: {}", s)),
         }
     }
 }
 
-impl ToString for Target {
-    fn to_string(&self) -> String {
-        self.to_str().to_owned()
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_str())
     }
 }
 
+/// Bonus for winning all eight tricks without having announced a capot.
+pub const CAPOT_BONUS: i32 = 250;
+
 /// Contract taken by a team.
 ///
-/// Composed of a trump suit and a target to reach.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// Composed of a trump mode and a target to reach.
+#[derive(Eq, PartialEq, Hash, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Contract {
     /// Initial author of the contract.
     pub author: pos::PlayerPos,
-    /// Trump suit for this game.
-    pub trump: cards::Suit,
+    /// Trump mode for this game.
+    pub trump: cards::Trump,
     /// Target for the contract.
     pub target: Target,
     /// Level of coinche:
@@ -118,21 +215,134 @@ pub struct Contract {
     /// * `1`: coinched
     /// * `2`: surcoinched
     pub coinche_level: i32,
+    /// Earlier bids from the same team with the same trump, that this contract raised.
+    ///
+    /// Oldest first; the contract itself is not included.
+    #[serde(default)]
+    pub raises: Vec<Raise>,
+}
+
+/// An earlier bid, raised by a later contract.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Raise {
+    /// Player who made the bid.
+    pub author: pos::PlayerPos,
+    /// Target of the bid.
+    pub target: Target,
 }
 
 impl Contract {
-    fn new(author: pos::PlayerPos, trump: cards::Suit, target: Target) -> Self {
+    fn new(author: pos::PlayerPos, trump: cards::Trump, target: Target) -> Self {
         Contract {
             author,
             trump,
             target,
             coinche_level: 0,
+            raises: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the team announced it would win every trick.
+    pub fn capot_announced(&self) -> bool {
+        self.target >= Target::ContractCapot
+    }
+
+    /// Returns `true` if the author announced it would win every trick alone.
+    pub fn generale_announced(&self) -> bool {
+        self.target == Target::Generale
+    }
+
+    /// Returns the player who first named this trump, before any raise.
+    pub fn named_by(&self) -> pos::PlayerPos {
+        self.raises
+            .first()
+            .map_or(self.author, |raise| raise.author)
+    }
+
+    /// Returns the target of the first bid in this trump, before any raise.
+    pub fn opening_target(&self) -> Target {
+        self.raises
+            .first()
+            .map_or(self.target, |raise| raise.target)
+    }
+
+    /// Returns the multiplier applied by coinches: `1`, `2` if coinched, `4` if surcoinched.
+    pub fn multiplier(&self) -> i32 {
+        1 << self.coinche_level
+    }
+
+    /// Returns the points at stake with this contract, including coinche multipliers.
+    pub fn value(&self) -> i32 {
+        self.target.score() * self.multiplier()
+    }
+}
+
+impl fmt::Display for Contract {
+    /// Writes the contract as its target, trump and coinche level (ex: `"120SA XX"`).
+    ///
+    /// With the alternate flag (`{:#}`), the author is written first (ex: `"P2: 100♥ X"`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}: ", self.author)?;
+        }
+        write!(f, "{}{}", self.target, self.trump)?;
+        match self.coinche_level {
+            0 => Ok(()),
+            1 => write!(f, " X"),
+            _ => write!(f, " XX"),
+        }
+    }
+}
+
+impl Contract {
+    /// Parses a contract from its textual form (ex: `"100♥"`, `"Capot♠ X"`, `"120SA XX"`).
+    pub fn parse(author: pos::PlayerPos, s: &str) -> Result<Self, String> {
+        let mut tokens = s.split_whitespace();
+        let head = tokens
+            .next()
+            .ok_or_else(|| format!("invalid contract: {}", s))?;
+
+        let coinche_level = match (tokens.next(), tokens.next()) {
+            (None, _) => 0,
+            (Some("X"), None) => 1,
+            (Some("XX"), None) => 2,
+            _ => return Err(format!("invalid contract: {}", s)),
+        };
+
+        // The target is the longest prefix that parses, the trump is the rest.
+        let (target, trump) = head
+            .char_indices()
+            .rev()
+            .filter_map(|(i, _)| {
+                let target = head[..i].parse::<Target>().ok()?;
+                let trump = head[i..].parse::<cards::Trump>().ok()?;
+                Some((target, trump))
+            })
+            .next()
+            .ok_or_else(|| format!("invalid contract: {}", s))?;
+
+        let mut contract = Contract::new(author, trump, target);
+        contract.coinche_level = coinche_level;
+        Ok(contract)
+    }
+}
+
+impl FromStr for Contract {
+    type Err = String;
+
+    /// Parses a contract, as written with `{}` or `{:#}` (ex: `"100♥ X"` or `"P2: 100♥ X"`).
+    ///
+    /// Without an author prefix, the author is `P0`: use `Contract::parse` to set another one.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.split_once(':') {
+            Some((author, contract)) => Contract::parse(author.trim().parse()?, contract),
+            None => Contract::parse(pos::PlayerPos::P0, s),
         }
     }
 }
 
 /// Current state of an auction
-#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
 pub enum AuctionState {
     /// Players are still bidding for the highest contract
     Bidding,
@@ -144,13 +354,182 @@ pub enum AuctionState {
     Cancelled,
 }
 
+/// A single action taken during an auction.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub enum AuctionEntry {
+    /// A player offered a new contract.
+    Bid {
+        /// Player who made the bid.
+        pos: pos::PlayerPos,
+        /// Trump mode for the offered contract.
+        trump: cards::Trump,
+        /// Target for the offered contract.
+        target: Target,
+    },
+    /// A player passed.
+    Pass {
+        /// Player who passed.
+        pos: pos::PlayerPos,
+    },
+    /// A player coinched (or surcoinched) the current contract.
+    Coinche {
+        /// Player who coinched.
+        pos: pos::PlayerPos,
+    },
+}
+
+/// An action a player may take during the auction.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub enum BidAction {
+    /// Pass the turn.
+    Pass,
+    /// Offer a new contract.
+    Bid(cards::Trump, Target),
+    /// Coinche (or surcoinche) the current contract.
+    Coinche,
+}
+
+impl AuctionEntry {
+    /// Returns the action taken, without the player.
+    pub fn action(&self) -> BidAction {
+        match *self {
+            AuctionEntry::Bid { trump, target, .. } => BidAction::Bid(trump, target),
+            AuctionEntry::Pass { .. } => BidAction::Pass,
+            AuctionEntry::Coinche { .. } => BidAction::Coinche,
+        }
+    }
+
+    /// Returns the player who took this action.
+    pub fn pos(&self) -> pos::PlayerPos {
+        match *self {
+            AuctionEntry::Bid { pos, .. }
+            | AuctionEntry::Pass { pos }
+            | AuctionEntry::Coinche { pos } => pos,
+        }
+    }
+}
+
+/// What a single player is allowed to see of the auction.
+///
+/// Other players' hands are never included, so this can be sent to clients as is.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AuctionView {
+    /// Player this view was made for.
+    pub pos: pos::PlayerPos,
+    /// Cards of this player.
+    pub hand: cards::Hand,
+    /// Player who dealt the cards.
+    pub dealer: pos::PlayerPos,
+    /// Every action taken so far, in order.
+    pub history: Vec<AuctionEntry>,
+    /// Last offered contract, if any.
+    pub contract: Option<Contract>,
+    /// Actions this player may take now.
+    pub legal_bids: LegalBids,
+}
+
+/// Summary of the auction after an action.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AuctionUpdate {
+    /// New state of the auction.
+    pub state: AuctionState,
+    /// Player expected to speak next, or `None` if the auction is over.
+    pub next_player: Option<pos::PlayerPos>,
+    /// `true` if the current contract may be coinched.
+    pub can_coinche: bool,
+    /// `true` if the current contract was coinched and may be surcoinched.
+    pub can_surcoinche: bool,
+}
+
+/// Actions available to a player during the auction.
+#[derive(Eq, PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct LegalBids {
+    /// Contracts the player may offer, in increasing order.
+    pub bids: Vec<(cards::Trump, Target)>,
+    /// `true` if the player may pass.
+    pub can_pass: bool,
+    /// `true` if the player may coinche (or surcoinche) the current contract.
+    pub can_coinche: bool,
+}
+
+/// Something that happened during the auction.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub enum AuctionEvent {
+    /// A player took an action.
+    Action(AuctionEntry),
+    /// The auction moved to a new state.
+    StateChanged(AuctionState),
+}
+
+/// Optional rules changing how the auction proceeds.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct AuctionRules {
+    /// Coinche "à la volée": defenders may coinche as soon as a contract exists,
+    /// without waiting for their turn (and the bidding team may surcoinche the same way).
+    ///
+    /// The first player to act wins the race.
+    pub coinche_out_of_turn: bool,
+    /// Once the bidding ends, defenders get a last chance to coinche.
+    ///
+    /// The auction cannot complete until both defenders declined to coinche
+    /// (see `Auction::decline_coinche`), or the window is closed with
+    /// `Auction::close_last_chance`.
+    #[serde(default)]
+    pub last_chance_coinche: bool,
+    /// Players who passed may not bid again later in the same auction.
+    ///
+    /// They may still pass, and coinche the current contract.
+    #[serde(default)]
+    pub no_reentry: bool,
+}
+
 /// Represents the entire auction process.
+///
+/// It can be serialized, to be stored and resumed later.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Auction {
-    history: Vec<Contract>,
+    contracts: Vec<Contract>,
+    entries: Vec<AuctionEntry>,
     pass_count: usize,
     first: pos::PlayerPos,
+    // Player who dealt the cards, if not the one before `first`.
+    // Auctions saved before the dealer was tracked have none.
+    #[serde(default)]
+    dealer: Option<pos::PlayerPos>,
     state: AuctionState,
     players: [cards::Hand; 4],
+    rules: AuctionRules,
+    // Player who coinched the current contract, if any.
+    coincher: Option<pos::PlayerPos>,
+    // Number of players from the bidding team who declined to surcoinche.
+    surcoinche_passes: usize,
+    // Defenders who may still use their last chance to coinche.
+    #[serde(default)]
+    last_chance: Vec<pos::PlayerPos>,
+    // Players who passed at least once during the bidding.
+    #[serde(default)]
+    passed: [bool; 4],
+    // Events not yet drained.
+    #[serde(skip)]
+    events: Vec<AuctionEvent>,
+}
+
+impl fmt::Debug for Auction {
+    /// Writes the auction state, without revealing the players hands.
+    ///
+    /// Use `Auction::debug_full` to include the hands.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug(f, false)
+    }
+}
+
+// Debug view of an auction, including the players hands.
+struct FullDebug<'a>(&'a Auction);
+
+impl<'a> fmt::Debug for FullDebug<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_debug(f, true)
+    }
 }
 
 /// Possible error occuring during an Auction.
@@ -159,40 +538,184 @@ pub enum BidError {
     /// The auction was closed and does not accept more contracts.
     AuctionClosed,
     /// A player tried bidding before his turn.
-    TurnError,
+    TurnError {
+        /// Player whose turn it actually is.
+        expected: pos::PlayerPos,
+    },
     /// The given bid was not higher than the previous one.
-    NonRaisedTarget,
+    NonRaisedTarget {
+        /// Contract currently held.
+        current: Box<Contract>,
+        /// Lowest target that may still be bid, if any.
+        minimum: Option<Target>,
+    },
     /// Cannot complete the auction when it is still running.
     AuctionRunning,
     /// No contract was offered during the auction, it cannot complete.
     NoContract,
     /// The contract was coinched too many times.
     OverCoinche,
+    /// Only the defending team may coinche a contract.
+    CoincheOwnContract,
+    /// Only the team holding the contract may surcoinche.
+    SurcoincheByDefense,
+    /// Defenders still have a last chance to coinche.
+    CoinchePending,
+    /// The player already passed, and may not bid anymore.
+    AlreadyPassed,
+    /// Cards can only be gathered from a cancelled auction.
+    NotCancelled,
 }
 
 impl fmt::Display for BidError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             BidError::AuctionClosed => write!(f, "auctions are closed"),
-            BidError::TurnError => write!(f, "invalid turn order"),
-            BidError::NonRaisedTarget => write!(f, "bid must be higher than current contract"),
+            BidError::TurnError { expected } => {
+                write!(f, "invalid turn order: waiting for {}", expected)
+            }
+            BidError::NonRaisedTarget {
+                ref current,
+                minimum: Some(minimum),
+            } => write!(
+                f,
+                "bid must be higher than current contract {} (at least {})",
+                current, minimum
+            ),
+            BidError::NonRaisedTarget { ref current, .. } => {
+                write!(f, "bid must be higher than current contract {}", current)
+            }
             BidError::AuctionRunning => write!(f, "the auction are still running"),
             BidError::NoContract => write!(f, "no contract was offered"),
             BidError::OverCoinche => write!(f, "contract is already sur-coinched"),
+            BidError::CoincheOwnContract => write!(f, "cannot coinche your own contract"),
+            BidError::SurcoincheByDefense => write!(f, "only the bidding team may sur-coinche"),
+            BidError::AlreadyPassed => write!(f, "player already passed"),
+            BidError::CoinchePending => write!(f, "defenders may still coinche"),
+            BidError::NotCancelled => write!(f, "the auction was not cancelled"),
         }
     }
 }
 
+// Failed transitions give the auction back, boxed to keep the results small,
+// so it can be resumed.
 impl Auction {
     /// Starts a new auction, starting with the player `first`.
     pub fn new(first: pos::PlayerPos) -> Self {
+        Auction::with_hands(first, super::deal_hands())
+    }
+
+    /// Starts a new auction, starting with the player `first`.
+    ///
+    /// Cards are dealt deterministically from the given seed.
+    pub fn new_seeded(first: pos::PlayerPos, seed: u64) -> Self {
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        Auction::with_hands(first, super::deal_seeded_hands(bytes))
+    }
+
+    /// Rebuilds an auction by applying the recorded actions, with the default rules.
+    ///
+    /// The dealer is the player before `first`.
+    /// Every action is validated: returns the first error encountered.
+    pub fn replay(
+        first: pos::PlayerPos,
+        hands: [cards::Hand; 4],
+        entries: &[AuctionEntry],
+    ) -> Result<Self, BidError> {
+        Auction::replay_with_rules(first, first.prev(), hands, AuctionRules::default(), entries)
+    }
+
+    /// Rebuilds an auction dealt by `dealer` by applying the recorded actions, with the given rules.
+    ///
+    /// Passes from defenders with a last chance to coinche decline it, as with `speak`.
+    /// Every action is validated: returns the first error encountered.
+    pub fn replay_with_rules(
+        first: pos::PlayerPos,
+        dealer: pos::PlayerPos,
+        hands: [cards::Hand; 4],
+        rules: AuctionRules,
+        entries: &[AuctionEntry],
+    ) -> Result<Self, BidError> {
+        let mut auction = Auction::with_hands(first, hands)
+            .with_rules(rules)
+            .with_dealer(dealer);
+        for entry in entries {
+            auction.speak(entry.pos(), entry.action())?;
+        }
+        auction.events.clear();
+        Ok(auction)
+    }
+
+    fn with_hands(first: pos::PlayerPos, hands: [cards::Hand; 4]) -> Self {
         Auction {
-            history: Vec::new(),
+            contracts: Vec::new(),
+            entries: Vec::new(),
             pass_count: 0,
             state: AuctionState::Bidding,
             first,
-            players: super::deal_hands(),
+            dealer: None,
+            players: hands,
+            rules: AuctionRules::default(),
+            coincher: None,
+            surcoinche_passes: 0,
+            last_chance: Vec::new(),
+            passed: [false; 4],
+            events: Vec::new(),
+        }
+    }
+
+    /// Sets the rules used by this auction.
+    pub fn with_rules(mut self, rules: AuctionRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Sets the dealer for this deal.
+    ///
+    /// By default, the dealer is the player before `first`.
+    pub fn with_dealer(mut self, dealer: pos::PlayerPos) -> Self {
+        self.dealer = Some(dealer);
+        self
+    }
+
+    /// Returns the player who dealt the cards.
+    pub fn dealer(&self) -> pos::PlayerPos {
+        self.dealer.unwrap_or_else(|| self.first.prev())
+    }
+
+    /// Returns the player who spoke first.
+    pub fn first(&self) -> pos::PlayerPos {
+        self.first
+    }
+
+    /// Returns the rules used by this auction.
+    pub fn rules(&self) -> AuctionRules {
+        self.rules
+    }
+
+    /// Returns a debug view of the auction, including the players hands.
+    ///
+    /// The regular `Debug` implementation hides them: only use this in trusted contexts.
+    pub fn debug_full(&self) -> impl fmt::Debug + '_ {
+        FullDebug(self)
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>, show_hands: bool) -> fmt::Result {
+        let mut s = f.debug_struct("Auction");
+        s.field("state", &self.state)
+            .field("first", &self.first)
+            .field("dealer", &self.dealer())
+            .field("history", &self.entries)
+            .field("contracts", &self.contracts)
+            .field("pass_count", &self.pass_count)
+            .field("rules", &self.rules);
+        if show_hands {
+            s.field("players", &self.players);
+        } else {
+            s.field("players", &format_args!("<hidden>"));
         }
+        s.finish()
     }
 
     /// Returns the current state of the auctions.
@@ -200,23 +723,124 @@ impl Auction {
         self.state
     }
 
-    fn can_bid(&self, target: Target) -> Result<(), BidError> {
+    /// Returns a digest of the public state of the auction, to detect desyncs.
+    ///
+    /// It depends on the first player, the dealer, the state and every action in order,
+    /// but not on the hands. Like `GameState::checksum`, it is stable across runs and platforms.
+    pub fn checksum(&self) -> u64 {
+        let values = [
+            self.first as u64,
+            self.dealer() as u64,
+            self.state as u64,
+            self.last_chance.len() as u64,
+        ];
+        let entries = self.entries.iter().map(|entry| {
+            let action = match entry.action() {
+                BidAction::Pass => 0,
+                BidAction::Coinche => 1,
+                BidAction::Bid(trump, target) => {
+                    let trump = cards::Trump::all()
+                        .iter()
+                        .position(|&t| t == trump)
+                        .unwrap();
+                    2 | (trump as u64) << 2 | (target as u64) << 5
+                }
+            };
+            action << 2 | entry.pos() as u64
+        });
+        game::checksum(values.iter().copied().chain(entries))
+    }
+
+    fn can_bid(&self, pos: pos::PlayerPos, target: Target) -> Result<(), BidError> {
         if self.state != AuctionState::Bidding {
             return Err(BidError::AuctionClosed);
         }
 
-        if !self.history.is_empty()
-            && target.score() <= self.history[self.history.len() - 1].target.score()
-        {
-            return Err(BidError::NonRaisedTarget);
+        if self.rules.no_reentry && self.passed[pos as usize] {
+            return Err(BidError::AlreadyPassed);
+        }
+
+        if let Some(contract) = self.current_contract() {
+            if target <= contract.target {
+                return Err(BidError::NonRaisedTarget {
+                    current: Box::new(contract.clone()),
+                    minimum: contract.target.next(),
+                });
+            }
         }
 
         Ok(())
     }
 
+    fn can_coinche(&self, pos: pos::PlayerPos) -> Result<(), BidError> {
+        let last_chance = self.last_chance.contains(&pos);
+        if self.state != AuctionState::Bidding
+            && self.state != AuctionState::Coinching
+            && !last_chance
+        {
+            return Err(BidError::AuctionClosed);
+        }
+
+        if !self.rules.coinche_out_of_turn && !last_chance && pos != self.next_player() {
+            return Err(BidError::TurnError {
+                expected: self.next_player(),
+            });
+        }
+
+        let contract = self.current_contract().ok_or(BidError::NoContract)?;
+        match contract.coinche_level {
+            0 if pos.is_partner(contract.author) => Err(BidError::CoincheOwnContract),
+            1 if !pos.is_partner(contract.author) => Err(BidError::SurcoincheByDefense),
+            level if level > 1 => Err(BidError::OverCoinche),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns what `pos` is allowed to see of the auction.
+    pub fn view_for(&self, pos: pos::PlayerPos) -> AuctionView {
+        AuctionView {
+            pos,
+            hand: self.players[pos as usize],
+            dealer: self.dealer(),
+            history: self.entries.clone(),
+            contract: self.current_contract().cloned(),
+            legal_bids: self.legal_bids(pos),
+        }
+    }
+
+    /// Returns every action `pos` may currently take.
+    pub fn legal_bids(&self, pos: pos::PlayerPos) -> LegalBids {
+        let running = self.state == AuctionState::Bidding || self.state == AuctionState::Coinching;
+        let in_turn = running && pos == self.next_player();
+
+        let mut bids = Vec::new();
+        if in_turn {
+            for target in Target::all().iter() {
+                if self.can_bid(pos, *target).is_ok() {
+                    for trump in cards::Trump::all().iter() {
+                        bids.push((*trump, *target));
+                    }
+                }
+            }
+        }
+
+        LegalBids {
+            bids,
+            can_pass: in_turn,
+            can_coinche: self.can_coinche(pos).is_ok(),
+        }
+    }
+
     /// Returns the player that is expected to play next.
+    ///
+    /// After a coinche, this is the next player from the bidding team
+    /// who has yet to decide whether to surcoinche.
     pub fn next_player(&self) -> pos::PlayerPos {
-        let base = if let Some(contract) = self.history.last() {
+        if let Some(coincher) = self.surcoinche_window() {
+            return coincher.next().next_n(2 * self.surcoinche_passes);
+        }
+
+        let base = if let Some(contract) = self.contracts.last() {
             contract.author.next()
         } else {
             self.first
@@ -224,43 +848,130 @@ impl Auction {
         base.next_n(self.pass_count)
     }
 
+    /// Takes the given action for the player `pos`.
+    ///
+    /// Dispatches to `bid`, `pass` or `coinche`.
+    pub fn apply(
+        &mut self,
+        pos: pos::PlayerPos,
+        action: BidAction,
+    ) -> Result<AuctionUpdate, BidError> {
+        match action {
+            BidAction::Pass => self.pass(pos),
+            BidAction::Bid(trump, target) => self.bid(pos, trump, target),
+            BidAction::Coinche => self.coinche(pos),
+        }
+    }
+
+    /// Returns the player expected to speak, or `None` once the auction is over or cancelled.
+    ///
+    /// While defenders may still use their last chance to coinche, returns one of them.
+    pub fn next_to_speak(&self) -> Option<pos::PlayerPos> {
+        if let Some(&pos) = self.last_chance.first() {
+            return Some(pos);
+        }
+        match self.state {
+            AuctionState::Bidding | AuctionState::Coinching => Some(self.next_player()),
+            _ => None,
+        }
+    }
+
+    /// Takes the given action for the player `pos`, as asked by `next_to_speak`.
+    ///
+    /// Like `apply`, except that a pass from a defender with a last chance to coinche declines it.
+    pub fn speak(
+        &mut self,
+        pos: pos::PlayerPos,
+        action: BidAction,
+    ) -> Result<AuctionUpdate, BidError> {
+        if action == BidAction::Pass && self.last_chance.contains(&pos) {
+            self.decline_coinche(pos)
+        } else {
+            self.apply(pos, action)
+        }
+    }
+
     /// Bid a new, higher contract.
+    ///
+    /// Returns the updated auction status.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(Display))
+    )]
     pub fn bid(
         &mut self,
         pos: pos::PlayerPos,
-        trump: cards::Suit,
+        trump: cards::Trump,
         target: Target,
-    ) -> Result<AuctionState, BidError> {
+    ) -> Result<AuctionUpdate, BidError> {
         if pos != self.next_player() {
-            return Err(BidError::TurnError);
+            return Err(BidError::TurnError {
+                expected: self.next_player(),
+            });
         }
 
-        self.can_bid(target)?;
+        self.can_bid(pos, target)?;
 
-        // If we're all the way to the top, there's nowhere else to go
-        if target == Target::ContractCapot {
-            self.state = AuctionState::Coinching;
+        let mut contract = Contract::new(pos, trump, target);
+        // Partners raising each other keep track of the earlier bids.
+        if let Some(last) = self.current_contract() {
+            if last.author.is_partner(pos) && last.trump == trump {
+                contract.raises = last.raises.clone();
+                contract.raises.push(Raise {
+                    author: last.author,
+                    target: last.target,
+                });
+            }
         }
-
-        let contract = Contract::new(pos, trump, target);
-        self.history.push(contract);
+        self.contracts.push(contract);
+        self.record(AuctionEntry::Bid { pos, trump, target });
         self.pass_count = 0;
 
-        // Only stops the bids if the guy asked for a capot
-        Ok(self.state)
+        // If we're all the way to the top, there's nowhere else to go
+        if target == Target::Generale {
+            self.set_state(AuctionState::Coinching);
+        }
+
+        // Only stops the bids if the guy asked for a generale
+        Ok(self.update())
     }
 
     /// Look at the last offered contract.
     ///
     /// Returns `None` if no contract was offered yet.
     pub fn current_contract(&self) -> Option<&Contract> {
-        if self.history.is_empty() {
+        if self.contracts.is_empty() {
             None
         } else {
-            Some(&self.history[self.history.len() - 1])
+            Some(&self.contracts[self.contracts.len() - 1])
         }
     }
 
+    /// Returns the events that happened since the last call, and clears them.
+    ///
+    /// Servers can use this after each action to log or broadcast the changes.
+    pub fn drain_events(&mut self) -> Vec<AuctionEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn record(&mut self, entry: AuctionEntry) {
+        self.entries.push(entry);
+        self.events.push(AuctionEvent::Action(entry));
+    }
+
+    fn set_state(&mut self, state: AuctionState) {
+        if state != self.state {
+            trace_event!(state = ?state, "auction state changed");
+            self.state = state;
+            self.events.push(AuctionEvent::StateChanged(state));
+        }
+    }
+
+    /// Returns every action taken so far, in order.
+    pub fn history(&self) -> &[AuctionEntry] {
+        &self.entries
+    }
+
     /// Returns the players cards.
     pub fn hands(&self) -> [cards::Hand; 4] {
         self.players
@@ -268,70 +979,176 @@ impl Auction {
 
     /// The current player passes his turn.
     ///
-    /// Returns the new auction state :
+    /// Returns the updated auction status, with the new state:
     ///
     /// * `AuctionState::Cancelled` if all players passed
-    /// * `AuctionState::Over` if 3 players passed in a row
+    /// * `AuctionState::Over` if 3 players passed in a row,
+    ///   or if both players of the bidding team declined to surcoinche
     /// * The previous state otherwise
-    pub fn pass(&mut self, pos: pos::PlayerPos) -> Result<AuctionState, BidError> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(Display))
+    )]
+    pub fn pass(&mut self, pos: pos::PlayerPos) -> Result<AuctionUpdate, BidError> {
         if pos != self.next_player() {
-            return Err(BidError::TurnError);
+            return Err(BidError::TurnError {
+                expected: self.next_player(),
+            });
+        }
+
+        self.record(AuctionEntry::Pass { pos });
+
+        if self.surcoinche_window().is_some() {
+            self.surcoinche_passes += 1;
+            if self.surcoinche_passes >= 2 {
+                self.set_state(AuctionState::Over);
+            }
+            return Ok(self.update());
         }
 
         self.pass_count += 1;
+        self.passed[pos as usize] = true;
 
         // After 3 passes, we're back to the contract author, and we can start.
-        if !self.history.is_empty() {
+        if let Some(contract) = self.contracts.last() {
             if self.pass_count >= 3 {
-                self.state = AuctionState::Over;
+                if self.rules.last_chance_coinche && contract.coinche_level == 0 {
+                    let author = contract.author;
+                    self.last_chance = vec![author.next(), author.prev()];
+                }
+                self.set_state(AuctionState::Over);
             }
         } else if self.pass_count >= 4 {
-            self.state = AuctionState::Cancelled;
+            self.set_state(AuctionState::Cancelled);
         };
 
-        Ok(self.state)
+        Ok(self.update())
     }
 
     /// Attempt to coinche the current contract.
-    pub fn coinche(&mut self, pos: pos::PlayerPos) -> Result<AuctionState, BidError> {
-        if pos != self.next_player() {
-            return Err(BidError::TurnError);
+    ///
+    /// Returns the updated auction status.
+    ///
+    /// With `AuctionRules::coinche_out_of_turn`, defenders may coinche (and the
+    /// bidding team may then surcoinche) even when it is not their turn.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(Display))
+    )]
+    pub fn coinche(&mut self, pos: pos::PlayerPos) -> Result<AuctionUpdate, BidError> {
+        self.can_coinche(pos)?;
+
+        let i = self.contracts.len() - 1;
+        self.contracts[i].coinche_level += 1;
+        self.record(AuctionEntry::Coinche { pos });
+        self.coincher = Some(pos);
+        self.last_chance.clear();
+        // Stop if we are already sur-coinching
+        self.set_state(if self.contracts[i].coinche_level == 2 {
+            AuctionState::Over
+        } else {
+            AuctionState::Coinching
+        });
+
+        Ok(self.update())
+    }
+
+    /// Returns the defenders who may still use their last chance to coinche.
+    ///
+    /// Only used with `AuctionRules::last_chance_coinche`.
+    pub fn pending_coinche(&self) -> &[pos::PlayerPos] {
+        &self.last_chance
+    }
+
+    /// A defender declines their last chance to coinche.
+    ///
+    /// This is recorded in the history as a pass.
+    /// Returns the updated auction status.
+    pub fn decline_coinche(&mut self, pos: pos::PlayerPos) -> Result<AuctionUpdate, BidError> {
+        if !self.last_chance.contains(&pos) {
+            return Err(BidError::AuctionClosed);
         }
 
-        if self.history.is_empty() {
-            return Err(BidError::NoContract);
+        self.last_chance.retain(|&p| p != pos);
+        self.record(AuctionEntry::Pass { pos });
+        Ok(self.update())
+    }
+
+    /// Closes the last chance to coinche, as if every defender declined.
+    ///
+    /// Useful when the defenders did not answer in time.
+    pub fn close_last_chance(&mut self) -> AuctionUpdate {
+        for pos in std::mem::take(&mut self.last_chance) {
+            self.record(AuctionEntry::Pass { pos });
         }
+        self.update()
+    }
 
-        let i = self.history.len() - 1;
-        if self.history[i].coinche_level > 1 {
-            return Err(BidError::OverCoinche);
+    fn update(&self) -> AuctionUpdate {
+        let running = self.state == AuctionState::Bidding || self.state == AuctionState::Coinching;
+        AuctionUpdate {
+            state: self.state,
+            next_player: if running {
+                Some(self.next_player())
+            } else {
+                None
+            },
+            can_coinche: (running || !self.last_chance.is_empty())
+                && self.current_contract().map(|c| c.coinche_level) == Some(0),
+            can_surcoinche: self.surcoinche_window().is_some(),
         }
+    }
 
-        self.history[i].coinche_level += 1;
-        // Stop if we are already sur-coinching
-        self.state = if self.history[i].coinche_level == 2 {
-            AuctionState::Over
-        } else {
-            AuctionState::Coinching
-        };
+    // Returns the coincher if the bidding team is deciding whether to surcoinche.
+    fn surcoinche_window(&self) -> Option<pos::PlayerPos> {
+        match self.current_contract() {
+            Some(contract)
+                if self.state == AuctionState::Coinching && contract.coinche_level == 1 =>
+            {
+                self.coincher
+            }
+            _ => None,
+        }
+    }
+
+    /// Consumes a cancelled auction to get back the dealt hands.
+    ///
+    /// If the auction was not cancelled, gives it back along with the error.
+    pub fn into_hands(self) -> Result<[cards::Hand; 4], (Box<Auction>, BidError)> {
+        if self.state != AuctionState::Cancelled {
+            return Err((Box::new(self), BidError::NotCancelled));
+        }
+
+        Ok(self.players)
+    }
 
-        Ok(self.state)
+    /// Consumes a cancelled auction to gather the cards into a deck.
+    ///
+    /// Hands are stacked one after the other, from the first player,
+    /// ready to be cut and dealt again.
+    pub fn into_deck(self) -> Result<cards::Deck, (Box<Auction>, BidError)> {
+        let first = self.first;
+        let hands = self.into_hands()?;
+        let hands: Vec<cards::Hand> = first.until_n(4).map(|p| hands[p as usize]).collect();
+        Ok(cards::Deck::gather(&hands))
     }
 
     /// Consumes a complete auction to enter the second game phase.
     ///
-    /// If the auction was ready, returns `Ok<GameState>`
-    pub fn complete(&mut self) -> Result<game::GameState, BidError> {
+    /// If the auction was ready, returns `Ok<GameState>`.
+    /// Otherwise, gives back the auction along with the error.
+    pub fn complete(mut self) -> Result<game::GameState, (Box<Auction>, BidError)> {
         if self.state != AuctionState::Over {
-            Err(BidError::AuctionRunning)
-        } else if self.history.is_empty() {
-            Err(BidError::NoContract)
-        } else {
-            Ok(game::GameState::new(
-                self.first,
-                self.players,
-                self.history.pop().expect("contract history empty"),
-            ))
+            return Err((Box::new(self), BidError::AuctionRunning));
+        }
+
+        if !self.last_chance.is_empty() {
+            return Err((Box::new(self), BidError::CoinchePending));
+        }
+
+        match self.contracts.pop() {
+            None => Err((Box::new(self), BidError::NoContract)),
+            Some(contract) => Ok(game::GameState::new(self.first, self.players, contract)),
         }
     }
 }
@@ -341,6 +1158,787 @@ mod tests {
     use super::*;
     use crate::{cards, pos};
 
+    #[test]
+    fn test_coinche_out_of_turn() {
+        let rules = AuctionRules {
+            coinche_out_of_turn: true,
+            ..AuctionRules::default()
+        };
+        let mut auction = Auction::new(pos::PlayerPos::P0).with_rules(rules);
+
+        // No contract yet.
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P3),
+            Err(BidError::NoContract)
+        );
+
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        // Partner cannot coinche.
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P2),
+            Err(BidError::CoincheOwnContract)
+        );
+        // P3 is a defender: it doesn't need to wait for P1.
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P3).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+        // Too late for P1.
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P1),
+            Err(BidError::SurcoincheByDefense)
+        );
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P2).map(|u| u.state),
+            Ok(AuctionState::Over)
+        );
+        assert_eq!(auction.current_contract().unwrap().coinche_level, 2);
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P0),
+            Err(BidError::AuctionClosed)
+        );
+    }
+
+    #[test]
+    fn test_last_chance_coinche() {
+        let rules = AuctionRules {
+            last_chance_coinche: true,
+            ..AuctionRules::default()
+        };
+        let mut auction = Auction::new(pos::PlayerPos::P0)
+            .with_rules(rules)
+            .with_dealer(pos::PlayerPos::P2);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        let update = auction.pass(pos::PlayerPos::P3).unwrap();
+        assert_eq!(update.state, AuctionState::Over);
+        assert!(update.can_coinche);
+        assert_eq!(
+            auction.pending_coinche(),
+            &[pos::PlayerPos::P1, pos::PlayerPos::P3]
+        );
+
+        let mut auction = match auction.complete() {
+            Err((auction, err)) => {
+                assert_eq!(err, BidError::CoinchePending);
+                *auction
+            }
+            Ok(_) => panic!("completed with a pending coinche"),
+        };
+
+        // Only defenders get a last chance.
+        assert_eq!(
+            auction.decline_coinche(pos::PlayerPos::P2),
+            Err(BidError::AuctionClosed)
+        );
+        assert!(auction.decline_coinche(pos::PlayerPos::P1).is_ok());
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P1),
+            Err(BidError::AuctionClosed)
+        );
+
+        // P3 coinches: the bidding team may now surcoinche.
+        let mut coinched = auction.clone();
+        assert_eq!(
+            coinched.coinche(pos::PlayerPos::P3).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+        assert_eq!(coinched.next_player(), pos::PlayerPos::P0);
+
+        let update = auction.close_last_chance();
+        assert!(!update.can_coinche);
+
+        // Declining and closing are recorded, so the auction can be replayed.
+        let replayed = Auction::replay_with_rules(
+            auction.first(),
+            auction.dealer(),
+            auction.hands(),
+            rules,
+            auction.history(),
+        )
+        .unwrap();
+        assert_eq!(replayed.history(), auction.history());
+        assert_eq!(replayed.dealer(), pos::PlayerPos::P2);
+        assert!(replayed.pending_coinche().is_empty());
+        assert_eq!(replayed.checksum(), auction.checksum());
+        assert!(auction.complete().is_ok());
+    }
+
+    #[test]
+    fn test_no_reentry() {
+        let rules = AuctionRules {
+            no_reentry: true,
+            ..AuctionRules::default()
+        };
+        let mut auction = Auction::new(pos::PlayerPos::P0).with_rules(rules);
+        auction.pass(pos::PlayerPos::P0).unwrap();
+        auction
+            .bid(
+                pos::PlayerPos::P1,
+                cards::Suit::Club.into(),
+                Target::Contract80,
+            )
+            .unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        auction
+            .bid(
+                pos::PlayerPos::P3,
+                cards::Suit::Heart.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+
+        // P0 passed earlier: it may only pass now.
+        assert!(auction.legal_bids(pos::PlayerPos::P0).bids.is_empty());
+        assert_eq!(
+            auction.bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Spade.into(),
+                Target::Contract100
+            ),
+            Err(BidError::AlreadyPassed)
+        );
+        auction.pass(pos::PlayerPos::P0).unwrap();
+
+        // P1 never passed, it can still raise.
+        assert!(auction
+            .bid(
+                pos::PlayerPos::P1,
+                cards::Suit::Club.into(),
+                Target::Contract100
+            )
+            .is_ok());
+
+        // Without the rule, P2 could re-enter.
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction.pass(pos::PlayerPos::P0).unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        auction
+            .bid(
+                pos::PlayerPos::P3,
+                cards::Suit::Heart.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        assert!(auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract100
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut auction = Auction::new(pos::PlayerPos::P1);
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction
+            .bid(
+                pos::PlayerPos::P2,
+                cards::Suit::Spade.into(),
+                Target::Contract80,
+            )
+            .unwrap();
+        auction.coinche(pos::PlayerPos::P3).unwrap();
+
+        let replayed =
+            Auction::replay(pos::PlayerPos::P1, auction.hands(), auction.history()).unwrap();
+        assert_eq!(replayed.checksum(), auction.checksum());
+        assert_eq!(replayed.history(), auction.history());
+        assert_eq!(replayed.get_state(), AuctionState::Coinching);
+        assert_eq!(replayed.next_player(), auction.next_player());
+        assert_eq!(replayed.current_contract(), auction.current_contract());
+
+        // Invalid sequences are rejected.
+        let entries = [
+            AuctionEntry::Pass {
+                pos: pos::PlayerPos::P1,
+            },
+            AuctionEntry::Pass {
+                pos: pos::PlayerPos::P1,
+            },
+        ];
+        assert_eq!(
+            Auction::replay(pos::PlayerPos::P1, auction.hands(), &entries).err(),
+            Some(BidError::TurnError {
+                expected: pos::PlayerPos::P2
+            })
+        );
+    }
+
+    #[test]
+    fn test_raise_chain() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        let heart = cards::Suit::Heart.into();
+        auction
+            .bid(pos::PlayerPos::P0, heart, Target::Contract80)
+            .unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction
+            .bid(pos::PlayerPos::P2, heart, Target::Contract100)
+            .unwrap();
+        auction.pass(pos::PlayerPos::P3).unwrap();
+        auction
+            .bid(pos::PlayerPos::P0, heart, Target::Contract120)
+            .unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        auction.pass(pos::PlayerPos::P3).unwrap();
+
+        let game = auction.complete().ok().unwrap();
+        let contract = game.contract();
+        assert_eq!(
+            contract.raises,
+            vec![
+                Raise {
+                    author: pos::PlayerPos::P0,
+                    target: Target::Contract80
+                },
+                Raise {
+                    author: pos::PlayerPos::P2,
+                    target: Target::Contract100
+                },
+            ]
+        );
+        assert_eq!(contract.named_by(), pos::PlayerPos::P0);
+        assert_eq!(contract.opening_target(), Target::Contract80);
+
+        // A bid in another trump starts a new chain.
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(pos::PlayerPos::P0, heart, Target::Contract80)
+            .unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction
+            .bid(
+                pos::PlayerPos::P2,
+                cards::Suit::Club.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        let contract = auction.current_contract().unwrap();
+        assert!(contract.raises.is_empty());
+        assert_eq!(contract.named_by(), pos::PlayerPos::P2);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        let action: BidAction =
+            serde_json::from_str(r#"{"Bid":[{"Suit":"Heart"},"Contract80"]}"#).unwrap();
+        assert_eq!(
+            action,
+            BidAction::Bid(cards::Suit::Heart.into(), Target::Contract80)
+        );
+
+        auction.apply(pos::PlayerPos::P0, action).unwrap();
+        assert_eq!(
+            auction.apply(pos::PlayerPos::P0, BidAction::Pass),
+            Err(BidError::TurnError {
+                expected: pos::PlayerPos::P1
+            })
+        );
+        auction
+            .apply(pos::PlayerPos::P1, BidAction::Coinche)
+            .unwrap();
+        assert_eq!(auction.history()[0].action(), action);
+        assert_eq!(auction.history()[1].action(), BidAction::Coinche);
+    }
+
+    #[test]
+    fn test_dealer() {
+        let auction = Auction::new(pos::PlayerPos::P2);
+        assert_eq!(auction.first(), pos::PlayerPos::P2);
+        assert_eq!(auction.dealer(), pos::PlayerPos::P1);
+
+        let auction = auction.with_dealer(pos::PlayerPos::P3);
+        assert_eq!(auction.dealer(), pos::PlayerPos::P3);
+        assert_eq!(auction.next_player(), pos::PlayerPos::P2);
+    }
+
+    #[test]
+    fn test_debug_redaction() {
+        let auction = Auction::new(pos::PlayerPos::P0);
+
+        let redacted = format!("{:?}", auction);
+        assert!(redacted.contains("<hidden>"));
+        assert!(redacted.contains("Bidding"));
+        assert!(!redacted.contains(&format!("{:?}", auction.hands()[0])));
+
+        let full = format!("{:?}", auction.debug_full());
+        assert!(!full.contains("<hidden>"));
+        assert!(full.contains(&format!("{:?}", auction.hands()[0])));
+    }
+
+    #[test]
+    fn test_coinche_eligibility() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        // P2 cannot coinche its partner.
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P2),
+            Err(BidError::CoincheOwnContract)
+        );
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P3).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+        // P3 cannot surcoinche: only the bidding team gets to decide.
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P3),
+            Err(BidError::TurnError {
+                expected: pos::PlayerPos::P0
+            })
+        );
+    }
+
+    #[test]
+    fn test_surcoinche_window() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P3).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+
+        // The bidding team now decides, starting after the coincher.
+        assert_eq!(auction.next_player(), pos::PlayerPos::P0);
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P1),
+            Err(BidError::TurnError {
+                expected: pos::PlayerPos::P0
+            })
+        );
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P0).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+        assert_eq!(auction.next_player(), pos::PlayerPos::P2);
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P2).map(|u| u.state),
+            Ok(AuctionState::Over)
+        );
+        assert_eq!(auction.current_contract().unwrap().coinche_level, 1);
+
+        // Same thing, but the partner surcoinches.
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract90,
+            )
+            .unwrap();
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P1).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+        assert_eq!(auction.next_player(), pos::PlayerPos::P2);
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        assert_eq!(
+            auction.coinche(pos::PlayerPos::P0).map(|u| u.state),
+            Ok(AuctionState::Over)
+        );
+        assert_eq!(auction.current_contract().unwrap().coinche_level, 2);
+    }
+
+    #[test]
+    fn test_generale() {
+        assert_eq!(Target::Generale.score(), 500);
+        assert_eq!("Générale".parse::<Target>(), Ok(Target::Generale));
+        assert!(Target::Generale.victory(162, true, true));
+        assert!(!Target::Generale.victory(162, true, false));
+        assert!(Target::ContractCapot.victory(162, true, false));
+        assert!(Target::ContractCapot.score() > CAPOT_BONUS);
+
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::ContractCapot,
+            )
+            .unwrap();
+        let contract = auction.current_contract().unwrap();
+        assert!(contract.capot_announced());
+        assert!(!contract.generale_announced());
+
+        // Capot can still be raised to a generale.
+        assert_eq!(
+            auction
+                .bid(
+                    pos::PlayerPos::P1,
+                    cards::Suit::Heart.into(),
+                    Target::Generale
+                )
+                .map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+        assert!(auction.current_contract().unwrap().generale_announced());
+    }
+
+    #[test]
+    fn test_target_order() {
+        assert!(Target::Contract80 < Target::Contract90);
+        assert!(Target::Contract250 < Target::ContractCapot);
+        assert_eq!(Target::all().iter().max(), Some(&Target::Generale));
+
+        let mut set = std::collections::HashSet::new();
+        let contract = Contract::new(
+            pos::PlayerPos::P0,
+            cards::Suit::Club.into(),
+            Target::Contract90,
+        );
+        set.insert(contract.clone());
+        assert!(set.contains(&contract));
+        let mut coinched = contract.clone();
+        coinched.coinche_level = 1;
+        assert!(!set.contains(&coinched));
+    }
+
+    #[test]
+    fn test_error_context() {
+        assert_eq!(Target::Contract80.next(), Some(Target::Contract90));
+        assert_eq!(Target::Contract250.next(), Some(Target::ContractCapot));
+        assert_eq!(Target::Generale.next(), None);
+
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        let err = auction.pass(pos::PlayerPos::P2).unwrap_err();
+        assert_eq!(err.to_string(), "invalid turn order: waiting for P0");
+
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Heart.into(),
+                Target::Contract100,
+            )
+            .unwrap();
+        let err = auction
+            .bid(
+                pos::PlayerPos::P1,
+                cards::Suit::Spade.into(),
+                Target::Contract90,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bid must be higher than current contract 100♥ (at least 110)"
+        );
+    }
+
+    #[test]
+    fn test_extended_targets() {
+        assert_eq!("170".parse::<Target>(), Ok(Target::Contract170));
+        assert_eq!(Target::Contract250.to_string(), "250");
+        assert_eq!(Target::Contract250.score(), 250);
+        assert!(Target::Contract170.victory(170, false, false));
+
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract250,
+            )
+            .unwrap();
+        assert_eq!(
+            auction.bid(
+                pos::PlayerPos::P1,
+                cards::Suit::Heart.into(),
+                Target::Contract160
+            ),
+            Err(BidError::NonRaisedTarget {
+                current: Box::new(Contract::new(
+                    pos::PlayerPos::P0,
+                    cards::Suit::Club.into(),
+                    Target::Contract250
+                )),
+                minimum: Some(Target::ContractCapot),
+            })
+        );
+        // Capot is a higher bid than 250.
+        assert_eq!(
+            auction
+                .bid(
+                    pos::PlayerPos::P1,
+                    cards::Suit::Heart.into(),
+                    Target::ContractCapot
+                )
+                .map(|u| u.state),
+            Ok(AuctionState::Bidding)
+        );
+    }
+
+    #[test]
+    fn test_resume_auction() {
+        let mut auction = Auction::new(pos::PlayerPos::P1);
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction
+            .bid(
+                pos::PlayerPos::P2,
+                cards::Suit::Spade.into(),
+                Target::Contract80,
+            )
+            .unwrap();
+
+        let json = serde_json::to_string(&auction).unwrap();
+        let mut restored: Auction = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.hands(), auction.hands());
+        assert_eq!(restored.history(), auction.history());
+        assert_eq!(restored.next_player(), pos::PlayerPos::P3);
+        assert_eq!(
+            restored.coinche(pos::PlayerPos::P3).map(|u| u.state),
+            Ok(AuctionState::Coinching)
+        );
+
+        let auction = auction.with_dealer(pos::PlayerPos::P3);
+        let json = serde_json::to_string(&auction).unwrap();
+        let restored: Auction = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.dealer(), pos::PlayerPos::P3);
+
+        // Auctions saved without a dealer were dealt by the player before the first one.
+        let mut value = serde_json::to_value(&auction).unwrap();
+        value.as_object_mut().unwrap().remove("dealer");
+        let restored: Auction = serde_json::from_value(value).unwrap();
+        assert_eq!(restored.dealer(), pos::PlayerPos::P0);
+    }
+
+    #[test]
+    fn test_legal_bids() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        let legal = auction.legal_bids(pos::PlayerPos::P0);
+        assert_eq!(legal.bids.len(), 6 * Target::all().len());
+        assert!(legal.can_pass);
+        assert!(!legal.can_coinche);
+
+        // Not P1's turn.
+        let legal = auction.legal_bids(pos::PlayerPos::P1);
+        assert!(legal.bids.is_empty());
+        assert!(!legal.can_pass);
+
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract250,
+            )
+            .unwrap();
+        let legal = auction.legal_bids(pos::PlayerPos::P1);
+        assert_eq!(legal.bids.len(), 6 * 2);
+        assert_eq!(legal.bids[0].1, Target::ContractCapot);
+        assert!(legal.can_coinche);
+        for &(trump, target) in legal.bids.iter() {
+            assert!(auction
+                .clone()
+                .bid(pos::PlayerPos::P1, trump, target)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_cancelled_auction() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        let hands = auction.hands();
+        auction.pass(pos::PlayerPos::P0).unwrap();
+
+        let (auction, err) = auction.into_hands().err().unwrap();
+        assert_eq!(err, BidError::NotCancelled);
+        let mut auction = *auction;
+
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        auction.pass(pos::PlayerPos::P3).unwrap();
+        assert_eq!(auction.clone().into_hands().ok(), Some(hands));
+
+        let deck = auction.into_deck().ok().unwrap();
+        assert_eq!(deck.len(), 32);
+    }
+
+    #[test]
+    fn test_contract_text() {
+        let p = pos::PlayerPos::P2;
+        for text in ["100♥", "Capot♠ X", "120SA XX", "80TA", "Generale♣"].iter() {
+            let contract = Contract::parse(p, text).unwrap();
+            assert_eq!(contract.to_string(), *text);
+        }
+
+        let contract = Contract::parse(p, "Capot♠ X").unwrap();
+        assert_eq!(contract.target, Target::ContractCapot);
+        assert_eq!(contract.trump, cards::Suit::Spade.into());
+        assert_eq!(contract.coinche_level, 1);
+
+        let contract = Contract::parse(p, "110H").unwrap();
+        assert_eq!(contract.trump, cards::Suit::Heart.into());
+        assert_eq!(contract.target, Target::Contract110);
+
+        let full = format!("{:#}", contract);
+        assert_eq!(full, "P2: 110♥");
+        let parsed: Contract = full.parse().unwrap();
+        assert_eq!(parsed.author, p);
+        assert_eq!(parsed.target, Target::Contract110);
+
+        assert!(Contract::parse(p, "").is_err());
+        assert!(Contract::parse(p, "85♥").is_err());
+        assert!(Contract::parse(p, "100♥ XXX").is_err());
+        assert!("P5: 100♥".parse::<Contract>().is_err());
+
+        // Without an author, the bare form still round-trips.
+        let bare = Contract::parse(pos::PlayerPos::P0, "100♥ X").unwrap();
+        assert_eq!(bare.to_string().parse::<Contract>(), Ok(bare));
+    }
+
+    #[test]
+    fn test_contract_value() {
+        let mut contract = Contract::new(
+            pos::PlayerPos::P0,
+            cards::Suit::Heart.into(),
+            Target::Contract120,
+        );
+        assert_eq!(contract.multiplier(), 1);
+        assert_eq!(contract.value(), 120);
+        contract.coinche_level = 1;
+        assert_eq!(contract.value(), 240);
+        contract.coinche_level = 2;
+        contract.target = Target::ContractCapot;
+        assert_eq!(contract.multiplier(), 4);
+        assert_eq!(contract.value(), 2000);
+    }
+
+    #[test]
+    fn test_complete_running() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction.pass(pos::PlayerPos::P0).unwrap();
+
+        // The auction is given back, and can go on.
+        let (mut auction, err) = auction.complete().err().unwrap();
+        assert_eq!(err, BidError::AuctionRunning);
+        assert!(auction.pass(pos::PlayerPos::P1).is_ok());
+    }
+
+    #[test]
+    fn test_auction_update() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        let update = auction.pass(pos::PlayerPos::P0).unwrap();
+        assert_eq!(update.next_player, Some(pos::PlayerPos::P1));
+        assert!(!update.can_coinche);
+
+        let update = auction
+            .bid(
+                pos::PlayerPos::P1,
+                cards::Suit::Club.into(),
+                Target::Contract80,
+            )
+            .unwrap();
+        assert_eq!(update.state, AuctionState::Bidding);
+        assert_eq!(update.next_player, Some(pos::PlayerPos::P2));
+        assert!(update.can_coinche);
+        assert!(!update.can_surcoinche);
+
+        let update = auction.coinche(pos::PlayerPos::P2).unwrap();
+        assert_eq!(update.state, AuctionState::Coinching);
+        assert_eq!(update.next_player, Some(pos::PlayerPos::P3));
+        assert!(!update.can_coinche);
+        assert!(update.can_surcoinche);
+
+        let update = auction.coinche(pos::PlayerPos::P3).unwrap();
+        assert_eq!(update.state, AuctionState::Over);
+        assert_eq!(update.next_player, None);
+        assert!(!update.can_surcoinche);
+    }
+
+    #[test]
+    fn test_events() {
+        let mut auction = Auction::new(pos::PlayerPos::P0);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Club.into(),
+                Target::Contract80,
+            )
+            .unwrap();
+        assert_eq!(
+            auction.drain_events(),
+            vec![AuctionEvent::Action(AuctionEntry::Bid {
+                pos: pos::PlayerPos::P0,
+                trump: cards::Suit::Club.into(),
+                target: Target::Contract80,
+            })]
+        );
+        assert!(auction.drain_events().is_empty());
+
+        auction.pass(pos::PlayerPos::P1).unwrap();
+        auction.pass(pos::PlayerPos::P2).unwrap();
+        auction.pass(pos::PlayerPos::P3).unwrap();
+        let events = auction.drain_events();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[3], AuctionEvent::StateChanged(AuctionState::Over));
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut a = Auction::new_seeded(pos::PlayerPos::P0, 1);
+        let mut b = Auction::new_seeded(pos::PlayerPos::P0, 2);
+        assert_eq!(a.checksum(), b.checksum());
+
+        for auction in [&mut a, &mut b].iter_mut() {
+            auction
+                .bid(
+                    pos::PlayerPos::P0,
+                    cards::Suit::Club.into(),
+                    Target::Contract80,
+                )
+                .unwrap();
+        }
+        assert_eq!(a.checksum(), b.checksum());
+
+        let before = a.checksum();
+        a.pass(pos::PlayerPos::P1).unwrap();
+        b.coinche(pos::PlayerPos::P1).unwrap();
+        assert!(a.checksum() != before);
+        assert!(a.checksum() != b.checksum());
+    }
+
+    #[test]
+    fn test_seeded_auction() {
+        let a = Auction::new_seeded(pos::PlayerPos::P0, 42);
+        let b = Auction::new_seeded(pos::PlayerPos::P2, 42);
+        let c = Auction::new_seeded(pos::PlayerPos::P0, 43);
+        assert_eq!(a.hands(), b.hands());
+        assert!(a.hands() != c.hands());
+    }
+
     #[test]
     fn test_auction() {
         let mut auction = Auction::new(pos::PlayerPos::P0);
@@ -348,48 +1946,120 @@ mod tests {
         assert!(auction.state == AuctionState::Bidding);
 
         // First three people pass.
-        assert_eq!(auction.pass(pos::PlayerPos::P0), Ok(AuctionState::Bidding));
-        assert_eq!(auction.pass(pos::PlayerPos::P1), Ok(AuctionState::Bidding));
-        assert_eq!(auction.pass(pos::PlayerPos::P2), Ok(AuctionState::Bidding));
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P0).map(|u| u.state),
+            Ok(AuctionState::Bidding)
+        );
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P1).map(|u| u.state),
+            Ok(AuctionState::Bidding)
+        );
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P2).map(|u| u.state),
+            Ok(AuctionState::Bidding)
+        );
 
-        assert_eq!(auction.pass(pos::PlayerPos::P1), Err(BidError::TurnError));
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P1),
+            Err(BidError::TurnError {
+                expected: pos::PlayerPos::P3
+            })
+        );
         assert_eq!(
             auction.coinche(pos::PlayerPos::P2),
-            Err(BidError::TurnError)
+            Err(BidError::TurnError {
+                expected: pos::PlayerPos::P3
+            })
         );
 
         // Someone bids.
         assert_eq!(
-            auction.bid(pos::PlayerPos::P3, cards::Suit::Heart, Target::Contract80),
+            auction
+                .bid(
+                    pos::PlayerPos::P3,
+                    cards::Trump::Suit(cards::Suit::Heart),
+                    Target::Contract80
+                )
+                .map(|u| u.state),
             Ok(AuctionState::Bidding)
         );
         assert_eq!(
             auction
-                .bid(pos::PlayerPos::P0, cards::Suit::Club, Target::Contract80)
+                .bid(
+                    pos::PlayerPos::P0,
+                    cards::Trump::Suit(cards::Suit::Club),
+                    Target::Contract80
+                )
                 .err(),
-            Some(BidError::NonRaisedTarget)
+            Some(BidError::NonRaisedTarget {
+                current: Box::new(Contract::new(
+                    pos::PlayerPos::P3,
+                    cards::Suit::Heart.into(),
+                    Target::Contract80
+                )),
+                minimum: Some(Target::Contract90),
+            })
         );
         assert_eq!(
             auction
-                .bid(pos::PlayerPos::P1, cards::Suit::Club, Target::Contract100)
+                .bid(
+                    pos::PlayerPos::P1,
+                    cards::Trump::Suit(cards::Suit::Club),
+                    Target::Contract100
+                )
                 .err(),
-            Some(BidError::TurnError)
+            Some(BidError::TurnError {
+                expected: pos::PlayerPos::P0
+            })
+        );
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P0).map(|u| u.state),
+            Ok(AuctionState::Bidding)
         );
-        assert_eq!(auction.pass(pos::PlayerPos::P0), Ok(AuctionState::Bidding));
         // Partner surbids
         assert_eq!(
-            auction.bid(pos::PlayerPos::P1, cards::Suit::Heart, Target::Contract100),
+            auction
+                .bid(
+                    pos::PlayerPos::P1,
+                    cards::Trump::Suit(cards::Suit::Heart),
+                    Target::Contract100
+                )
+                .map(|u| u.state),
+            Ok(AuctionState::Bidding)
+        );
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P2).map(|u| u.state),
+            Ok(AuctionState::Bidding)
+        );
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P3).map(|u| u.state),
             Ok(AuctionState::Bidding)
         );
-        assert_eq!(auction.pass(pos::PlayerPos::P2), Ok(AuctionState::Bidding));
-        assert_eq!(auction.pass(pos::PlayerPos::P3), Ok(AuctionState::Bidding));
-        assert_eq!(auction.pass(pos::PlayerPos::P0), Ok(AuctionState::Over));
+        assert_eq!(
+            auction.pass(pos::PlayerPos::P0).map(|u| u.state),
+            Ok(AuctionState::Over)
+        );
 
         assert!(auction.state == AuctionState::Over);
 
-        match auction.complete() {
-            Err(_) => assert!(false),
-            _ => {}
-        }
+        let history = auction.history();
+        assert_eq!(history.len(), 9);
+        assert_eq!(
+            history[0],
+            AuctionEntry::Pass {
+                pos: pos::PlayerPos::P0
+            }
+        );
+        assert_eq!(
+            history[3],
+            AuctionEntry::Bid {
+                pos: pos::PlayerPos::P3,
+                trump: cards::Suit::Heart.into(),
+                target: Target::Contract80,
+            }
+        );
+        assert_eq!(history[8].pos(), pos::PlayerPos::P0);
+
+        assert!(auction.complete().is_ok());
     }
 }
@@ -1,12 +1,12 @@
 //! This module represents a basic, rule-agnostic 32-cards system.
 
 use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
+use std::fmt;
 use std::num::Wrapping;
 use std::str::FromStr;
-use std::string::ToString;
 
 /// One of the four Suits: Heart, Spade, Diamond, Club.
-#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
 #[repr(u32)]
 pub enum Suit {
     /// The suit of hearts.
@@ -41,16 +41,16 @@ impl Suit {
     }
 }
 
-impl ToString for Suit {
-    /// Returns a UTF-8 character representing the suit (♥, ♠, ♦ or ♣).
-    fn to_string(&self) -> String {
-        match &self {
+impl fmt::Display for Suit {
+    /// Writes a UTF-8 character representing the suit (♥, ♠, ♦ or ♣).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
             Suit::Heart => "♥",
             Suit::Spade => "♠",
             Suit::Diamond => "♦",
             Suit::Club => "♣",
-        }
-        .to_owned()
+        };
+        write!(f, "{}", s)
     }
 }
 
@@ -68,6 +68,84 @@ impl FromStr for Suit {
     }
 }
 
+/// Trump mode of a game: a single trump suit, no trump at all, or every suit as trump.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Trump {
+    /// A single suit is trump.
+    Suit(Suit),
+    /// No suit is trump (Sans-Atout).
+    NoTrump,
+    /// Every suit is trump (Tout-Atout).
+    AllTrump,
+}
+
+impl Trump {
+    /// Returns all trump modes: each suit, then no trump and all trump.
+    pub fn all() -> [Trump; 6] {
+        [
+            Trump::Suit(Suit::Heart),
+            Trump::Suit(Suit::Spade),
+            Trump::Suit(Suit::Diamond),
+            Trump::Suit(Suit::Club),
+            Trump::NoTrump,
+            Trump::AllTrump,
+        ]
+    }
+
+    /// Returns the trump suit, if there is exactly one.
+    pub fn suit(self) -> Option<Suit> {
+        match self {
+            Trump::Suit(suit) => Some(suit),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if cards of the given suit are trumps.
+    pub fn is_trump(self, suit: Suit) -> bool {
+        match self {
+            Trump::Suit(trump) => trump == suit,
+            Trump::NoTrump => false,
+            Trump::AllTrump => true,
+        }
+    }
+}
+
+impl From<Suit> for Trump {
+    fn from(suit: Suit) -> Self {
+        Trump::Suit(suit)
+    }
+}
+
+impl fmt::Display for Trump {
+    /// Writes the suit character, `SA` for no trump, or `TA` for all trump.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trump::Suit(suit) => write!(f, "{}", suit),
+            Trump::NoTrump => write!(f, "SA"),
+            Trump::AllTrump => write!(f, "TA"),
+        }
+    }
+}
+
+impl FromStr for Trump {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "SA" | "sa" | "NT" | "nt" | "NoTrump" => Ok(Trump::NoTrump),
+            "TA" | "ta" | "AT" | "at" | "AllTrump" => Ok(Trump::AllTrump),
+            "♥" => Ok(Trump::Suit(Suit::Heart)),
+            "♠" => Ok(Trump::Suit(Suit::Spade)),
+            "♦" => Ok(Trump::Suit(Suit::Diamond)),
+            "♣" => Ok(Trump::Suit(Suit::Club)),
+            s => s
+                .parse()
+                .map(Trump::Suit)
+                .map_err(|_| format!("invalid trump: {}", s)),
+        }
+    }
+}
+
 /// Rank of a card in a suit.
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 #[repr(u32)]
@@ -122,6 +200,34 @@ impl Rank {
         }
     }
 
+    /// Iterates on all ranks, from the weakest to the strongest trump.
+    ///
+    /// Use `.rev()` to iterate from the strongest.
+    pub fn by_trump_strength() -> impl DoubleEndedIterator<Item = Rank> + ExactSizeIterator {
+        IntoIterator::into_iter([
+            Rank::Rank7,
+            Rank::Rank8,
+            Rank::RankQ,
+            Rank::RankK,
+            Rank::RankX,
+            Rank::RankA,
+            Rank::Rank9,
+            Rank::RankJ,
+        ])
+    }
+
+    /// Iterates on all ranks, from the weakest to the strongest non-trump.
+    ///
+    /// Use `.rev()` to iterate from the strongest.
+    pub fn by_plain_strength() -> impl DoubleEndedIterator<Item = Rank> + ExactSizeIterator {
+        (0..8).map(Rank::from_n)
+    }
+
+    /// Returns the number corresponding to this rank (the opposite of `from_n`).
+    pub fn index(self) -> usize {
+        (self as u32).trailing_zeros() as usize
+    }
+
     // Return the enum by its discriminant.
     fn from_discriminant(rank: u32) -> Self {
         match rank {
@@ -138,10 +244,10 @@ impl Rank {
     }
 }
 
-impl ToString for Rank {
-    /// Returns a character representing the given rank.
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Rank {
+    /// Writes a character representing the given rank.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
             Rank::Rank7 => "7",
             Rank::Rank8 => "8",
             Rank::Rank9 => "9",
@@ -150,8 +256,8 @@ impl ToString for Rank {
             Rank::RankK => "K",
             Rank::RankX => "X",
             Rank::RankA => "A",
-        }
-        .to_owned()
+        };
+        write!(f, "{}", s)
     }
 }
 
@@ -214,17 +320,17 @@ impl Card {
     }
 }
 
-impl ToString for Card {
-    /// Returns a string representation of the card (ex: "7♦").
-    fn to_string(&self) -> String {
-        let r = self.rank();
-        let s = self.suit();
-        r.to_string() + &s.to_string()
+impl fmt::Display for Card {
+    /// Writes a string representation of the card (ex: "7♦").
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank(), self.suit())
     }
 }
 
 /// Represents an unordered set of cards.
-#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize, Default)]
+#[derive(
+    Eq, PartialEq, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize, Default,
+)]
 pub struct Hand(u32);
 
 impl Hand {
@@ -268,6 +374,26 @@ impl Hand {
         self.0 == 0
     }
 
+    /// Returns the cards of `self` in the given suit.
+    pub fn suit_cards(self, suit: Suit) -> Hand {
+        Hand(self.0 & (RANK_MASK * suit as u32))
+    }
+
+    /// Returns the cards contained in either `self` or `other`.
+    pub fn union(self, other: Hand) -> Hand {
+        Hand(self.0 | other.0)
+    }
+
+    /// Returns the cards contained in both `self` and `other`.
+    pub fn intersection(self, other: Hand) -> Hand {
+        Hand(self.0 & other.0)
+    }
+
+    /// Returns the cards contained in `self` but not in `other`.
+    pub fn difference(self, other: Hand) -> Hand {
+        Hand(self.0 & !other.0)
+    }
+
     /// Returns a card from `self`.
     ///
     /// Returns an invalid card if `self` is empty.
@@ -305,21 +431,49 @@ impl Hand {
 
     /// Returns the number of cards in `self`.
     pub fn size(self) -> usize {
-        self.list().len()
+        self.0.count_ones() as usize
+    }
+
+    /// Returns `n` cards from `self`, picked at random.
+    ///
+    /// # Panics
+    ///
+    /// If `n > self.size()`.
+    pub fn pick_random_n<R: Rng + ?Sized>(self, rng: &mut R, n: usize) -> Hand {
+        let mut remaining = self.0;
+        let mut size = self.size();
+        if n > size {
+            panic!("cannot pick {} cards from a hand of {}", n, size);
+        }
+
+        let mut picked = Hand::new();
+        for _ in 0..n {
+            // Find the k-th card still in the hand.
+            let mut bits = remaining;
+            for _ in 0..rng.gen_range(0..size) {
+                bits &= bits - 1;
+            }
+            let card = bits & bits.wrapping_neg();
+
+            picked.0 |= card;
+            remaining &= !card;
+            size -= 1;
+        }
+
+        picked
     }
 }
 
-impl ToString for Hand {
-    /// Returns a string representation of `self`.
-    fn to_string(&self) -> String {
-        let mut s = "[".to_owned();
+impl fmt::Display for Hand {
+    /// Writes a string representation of `self`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
 
-        for c in &(*self).list() {
-            s += &c.to_string();
-            s += ",";
+        for c in &self.list() {
+            write!(f, "{},", c)?;
         }
 
-        s + "]"
+        write!(f, "]")
     }
 }
 
@@ -348,6 +502,26 @@ impl Deck {
         d
     }
 
+    /// Returns a deck made of the given cards.
+    ///
+    /// The last card will be drawn first.
+    pub fn from_cards(cards: Vec<Card>) -> Self {
+        Deck { cards }
+    }
+
+    /// Returns a deck made by gathering the given hands, one after the other.
+    pub fn gather(hands: &[Hand]) -> Self {
+        Deck::from_cards(hands.iter().flat_map(|hand| hand.list()).collect())
+    }
+
+    /// Cuts the deck: the top `n` cards are moved to the bottom.
+    ///
+    /// # Panics
+    /// If `n > self.len()`
+    pub fn cut(&mut self, n: usize) {
+        self.cards.rotate_right(n);
+    }
+
     /// Shuffle this deck.
     pub fn shuffle(&mut self) {
         self.shuffle_from(thread_rng());
@@ -400,16 +574,15 @@ impl Deck {
     }
 }
 
-impl ToString for Deck {
-    fn to_string(&self) -> String {
-        let mut s = "[".to_owned();
+impl fmt::Display for Deck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
 
         for c in &self.cards {
-            s += &c.to_string();
-            s += ",";
+            write!(f, "{},", c)?;
         }
 
-        s + "]"
+        write!(f, "]")
     }
 }
 
@@ -429,6 +602,7 @@ mod tests {
             for r in 0..8 {
                 let rank = Rank::from_n(r);
                 let card = Card::new(suit, rank);
+                assert!(rank.index() == r as usize);
                 assert!(card.rank() == rank);
                 assert!(card.suit() == suit);
             }
@@ -467,6 +641,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_trump() {
+        for s in 0..4 {
+            let suit = Suit::from_n(s);
+            let trump = Trump::from(suit);
+            assert_eq!(trump.to_string().parse::<Trump>(), Ok(trump));
+            assert!(trump.is_trump(suit));
+            assert!(!Trump::NoTrump.is_trump(suit));
+            assert!(Trump::AllTrump.is_trump(suit));
+        }
+
+        assert_eq!("h".parse::<Trump>(), Ok(Trump::Suit(Suit::Heart)));
+        assert_eq!("SA".parse::<Trump>(), Ok(Trump::NoTrump));
+        assert_eq!("TA".parse::<Trump>(), Ok(Trump::AllTrump));
+        assert!("X".parse::<Trump>().is_err());
+    }
+
+    #[test]
+    fn test_pick_random() {
+        let mut rng = StdRng::from_seed([4; 32]);
+        let hands = crate::deal_seeded_hands([2; 32]);
+
+        for hand in hands.iter() {
+            for n in 0..9 {
+                let picked = hand.pick_random_n(&mut rng, n);
+                assert_eq!(picked.size(), n);
+                for card in picked.list() {
+                    assert!(hand.has(card));
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_deck() {
         let mut deck = Deck::new();
@@ -484,16 +691,32 @@ mod tests {
             assert!(*c == 1);
         }
     }
+
+    #[test]
+    fn test_gather_and_cut() {
+        let hands = crate::deal_seeded_hands([9; 32]);
+        let mut deck = Deck::gather(&hands);
+        assert_eq!(deck.len(), 32);
+
+        // The top card is the last card of the last hand.
+        let top = *hands[3].list().last().unwrap();
+        deck.cut(5);
+        assert_eq!(deck.cards[4], top);
+
+        let mut new_hands = [Hand::new(); 4];
+        deck.deal_each(&mut new_hands, 8);
+        assert!(deck.is_empty());
+    }
 }
 
-#[cfg(feature = "use_bench")]
+#[cfg(all(test, feature = "use_bench"))]
 mod benchs {
-    use deal_seeded_hands;
+    use crate::deal_seeded_hands;
     use test::Bencher;
 
     #[bench]
     fn bench_deal(b: &mut Bencher) {
-        let seed = &[1, 2, 3, 4, 5];
+        let seed = [1; 32];
         b.iter(|| {
             deal_seeded_hands(seed);
         });
@@ -501,7 +724,7 @@ mod benchs {
 
     #[bench]
     fn bench_list_hand(b: &mut Bencher) {
-        let seed = &[1, 2, 3, 4, 5];
+        let seed = [1; 32];
         let hands = deal_seeded_hands(seed);
         b.iter(|| {
             for hand in hands.iter() {
@@ -512,11 +735,11 @@ mod benchs {
 
     #[bench]
     fn bench_del_add_check(b: &mut Bencher) {
-        let seed = &[1, 2, 3, 4, 5];
+        let seed = [1; 32];
         let hands = deal_seeded_hands(seed);
         let cards: Vec<_> = hands.iter().map(|h| h.list()).collect();
         b.iter(|| {
-            let mut hands = hands.clone();
+            let mut hands = hands;
             for (hand, cards) in hands.iter_mut().zip(cards.iter()) {
                 for c in cards.iter() {
                     hand.remove(*c);
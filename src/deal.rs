@@ -0,0 +1,238 @@
+//! Follow a whole deal, from the auction to the result, with a single object.
+
+use std::fmt;
+
+use super::bid;
+use super::cards;
+use super::game;
+use super::pos;
+use super::session;
+
+/// An action a player may take at any point of the deal.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Action {
+    /// Speak during the auction.
+    Bid(bid::BidAction),
+    /// Play a card.
+    Play(cards::Card),
+}
+
+impl From<bid::BidAction> for Action {
+    fn from(action: bid::BidAction) -> Self {
+        Action::Bid(action)
+    }
+}
+
+impl From<cards::Card> for Action {
+    fn from(card: cards::Card) -> Self {
+        Action::Play(card)
+    }
+}
+
+/// Error that can occur when applying an action to a deal.
+#[derive(Eq, PartialEq, Debug)]
+pub enum GameError {
+    /// The auction rejected the action.
+    Bid(bid::BidError),
+    /// The card could not be played.
+    Play(game::PlayError),
+    /// The action does not belong to the current phase (ex: playing a card during the auction).
+    WrongPhase,
+    /// The match rejected the deal.
+    Match(session::MatchError),
+}
+
+impl From<bid::BidError> for GameError {
+    fn from(err: bid::BidError) -> Self {
+        GameError::Bid(err)
+    }
+}
+
+impl From<game::PlayError> for GameError {
+    fn from(err: game::PlayError) -> Self {
+        GameError::Play(err)
+    }
+}
+
+impl From<session::MatchError> for GameError {
+    fn from(err: session::MatchError) -> Self {
+        match err {
+            session::MatchError::Bid(err) => GameError::Bid(err),
+            err => GameError::Match(err),
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            GameError::Bid(ref err) => write!(f, "{}", err),
+            GameError::Play(ref err) => write!(f, "{}", err),
+            GameError::WrongPhase => write!(f, "this action is not allowed now"),
+            GameError::Match(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// A deal, in any of its phases.
+///
+/// `apply` moves on to the next phase as soon as the current one is over.
+// Only one deal is kept at a time: the size of the game state is not worth a box.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub enum Game {
+    /// Players are bidding.
+    Bidding(bid::Auction),
+    /// Every player passed: the cards must be dealt again.
+    Cancelled(bid::Auction),
+    /// Players are playing cards.
+    Playing(game::GameState),
+    /// The deal is over: the finished game is kept along with its result.
+    Done(game::GameState, game::GameResult),
+}
+
+impl From<bid::Auction> for Game {
+    fn from(auction: bid::Auction) -> Self {
+        Game::Bidding(auction)
+    }
+}
+
+impl Game {
+    /// Starts a new deal, with a random distribution and `first` speaking first.
+    pub fn new(first: pos::PlayerPos) -> Self {
+        Game::Bidding(bid::Auction::new(first))
+    }
+
+    /// Returns the player expected to act, or `None` if the deal is over or cancelled.
+    ///
+    /// While defenders may still use their last chance to coinche, returns one of them.
+    pub fn next_player(&self) -> Option<pos::PlayerPos> {
+        match *self {
+            Game::Bidding(ref auction) => auction.next_to_speak(),
+            Game::Playing(ref game) => Some(game.next_player()),
+            Game::Cancelled(_) | Game::Done(..) => None,
+        }
+    }
+
+    /// Returns the game being played, or the finished one if the deal is over.
+    pub fn game(&self) -> Option<&game::GameState> {
+        match *self {
+            Game::Playing(ref game) | Game::Done(ref game, _) => Some(game),
+            _ => None,
+        }
+    }
+
+    /// Returns the result of the deal, if it is over.
+    pub fn result(&self) -> Option<&game::GameResult> {
+        match *self {
+            Game::Done(_, ref result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Takes the given action for the player `pos`.
+    ///
+    /// During the auction, a pass from a defender with a last chance to coinche declines it.
+    /// When the auction is over, the game starts; when the last card is played, the deal is done.
+    ///
+    /// If the game cannot start from the finished auction, the error is returned and the
+    /// auction is kept.
+    pub fn apply(&mut self, pos: pos::PlayerPos, action: Action) -> Result<(), GameError> {
+        match (&mut *self, action) {
+            (Game::Bidding(auction), Action::Bid(action)) => {
+                auction.speak(pos, action)?;
+            }
+            (Game::Playing(game), Action::Play(card)) => {
+                if let game::TrickResult::TrickOver(_, result) = game.play_card(pos, card)? {
+                    if result != game::GameResult::Nothing {
+                        *self = Game::Done(game.clone(), result);
+                    }
+                }
+                return Ok(());
+            }
+            _ => return Err(GameError::WrongPhase),
+        }
+
+        // The auction may be over: start the next phase.
+        let auction = match *self {
+            Game::Bidding(ref auction) if auction.pending_coinche().is_empty() => auction,
+            _ => return Ok(()),
+        };
+        match auction.get_state() {
+            bid::AuctionState::Over => match auction.clone().complete() {
+                Ok(game) => *self = Game::Playing(game),
+                Err((_, err)) => return Err(err.into()),
+            },
+            bid::AuctionState::Cancelled => *self = Game::Cancelled(auction.clone()),
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, game, pos};
+
+    #[test]
+    fn test_game() {
+        let mut deal = Game::new(pos::PlayerPos::P0);
+        assert_eq!(deal.next_player(), Some(pos::PlayerPos::P0));
+
+        let card = cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7);
+        assert_eq!(
+            deal.apply(pos::PlayerPos::P0, card.into()),
+            Err(GameError::WrongPhase)
+        );
+        assert_eq!(
+            deal.apply(pos::PlayerPos::P1, bid::BidAction::Pass.into()),
+            Err(GameError::Bid(bid::BidError::TurnError {
+                expected: pos::PlayerPos::P0
+            }))
+        );
+
+        let contract = bid::BidAction::Bid(cards::Suit::Spade.into(), bid::Target::Contract80);
+        deal.apply(pos::PlayerPos::P0, contract.into()).unwrap();
+        for p in pos::PlayerPos::P1.until_n(3) {
+            deal.apply(p, bid::BidAction::Pass.into()).unwrap();
+        }
+        assert!(matches!(deal, Game::Playing(_)));
+
+        while let Some(p) = deal.next_player() {
+            let card = match deal {
+                Game::Playing(ref game) => game.legal_moves(p).unwrap().get_card(),
+                _ => unreachable!(),
+            };
+            assert_eq!(
+                deal.apply(p.next(), card.into()),
+                Err(GameError::Play(game::PlayError::TurnError { expected: p }))
+            );
+            deal.apply(p, card.into()).unwrap();
+        }
+        assert!(matches!(
+            deal.result(),
+            Some(game::GameResult::GameOver { .. })
+        ));
+        // The finished game is kept, with its scores.
+        let game = deal.game().unwrap();
+        assert!(game.is_over());
+        assert_eq!(game.tricks_remaining(), 0);
+        match deal.result() {
+            Some(&game::GameResult::GameOver { points, .. }) => assert_eq!(game.points(), points),
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            deal.apply(pos::PlayerPos::P0, bid::BidAction::Pass.into()),
+            Err(GameError::WrongPhase)
+        );
+
+        // Every player passes.
+        let mut deal = Game::new(pos::PlayerPos::P2);
+        for p in pos::PlayerPos::P2.until_n(4) {
+            deal.apply(p, bid::BidAction::Pass.into()).unwrap();
+        }
+        assert!(matches!(deal, Game::Cancelled(_)));
+        assert_eq!(deal.next_player(), None);
+    }
+}
@@ -1,27 +1,69 @@
 //! Module for the card game, after auctions are complete.
 use std::fmt;
+use std::sync::Arc;
 
+use rand::Rng;
+
+use super::announces;
 use super::bid;
 use super::cards;
 use super::points;
 use super::pos;
+use super::rules;
+use super::solver;
+use super::tracking;
 use super::trick;
 
+/// Most tricks that may remain when a claim is made (see `GameState::claim`).
+pub const MAX_CLAIM_TRICKS: usize = 4;
+
 /// Describes the state of a coinche game, ready to play a card.
+///
+/// Cloning a game does not allocate while event recording is disabled (see `with_events`),
+/// so searches can clone it freely.
 #[derive(Clone)]
 pub struct GameState {
     players: [cards::Hand; 4],
 
     current: pos::PlayerPos,
 
-    contract: bid::Contract,
+    // Shared, so cloning the game does not allocate.
+    contract: Arc<bid::Contract>,
 
     points: [i32; 2],
-    tricks: Vec<trick::Trick>,
+    // Only the first `trick_count` tricks were started.
+    tricks: [trick::Trick; 8],
+    trick_count: usize,
+
+    // Player holding the King and Queen of trump, if any.
+    belote: Option<pos::PlayerPos>,
+
+    // Declarations found in each hand, if announces are played.
+    announces: Option<Arc<[Vec<announces::Announce>; 4]>>,
+
+    rules: rules::RuleSet,
+
+    // Events not yet drained.
+    events: Vec<GameEvent>,
+    record_events: bool,
+
+    // Zobrist hash of the cards played, current player and contract.
+    hash: u64,
+
+    // Team who conceded the deal, if any.
+    conceded: Option<pos::Team>,
+
+    // First illegal card played, if renounces are recorded.
+    renounce: Option<(pos::PlayerPos, cards::Card)>,
+
+    // Caller payloads, by trick index and player (`None` for the trick itself).
+    metadata: Arc<Vec<(usize, Option<pos::PlayerPos>, String)>>,
+
+    scoring: Arc<dyn points::ScoringScheme + Send + Sync>,
 }
 
 /// Result of a game.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum GameResult {
     /// The game is still playing
     Nothing,
@@ -34,7 +76,199 @@ pub enum GameResult {
         winners: pos::Team,
         /// Score for this game
         scores: [i32; 2],
+        /// Team who held the belote (King and Queen of trump), if any
+        belote: Option<pos::Team>,
+        /// Declaration points for each team
+        declarations: [i32; 2],
+        /// Contract played
+        contract: bid::Contract,
+        /// `true` if the team holding the contract fulfilled it
+        fulfilled: bool,
+        /// Points counted for the contract (including belote and declarations),
+        /// minus the points required by the target.
+        ///
+        /// `None` for a capot or a generale, which are won on tricks rather than points.
+        margin: Option<i32>,
+        /// Team who won the last trick
+        last_trick: pos::Team,
+        /// Team who earned the dix de der, if it was awarded
+        dix_de_der: Option<pos::Team>,
+        /// Team who won every trick, if any
+        capot: Option<pos::Team>,
+        /// Multiplier applied by coinches
+        multiplier: i32,
+        /// Team who conceded the deal, if any
+        conceded: Option<pos::Team>,
+        /// Team who first played an illegal card, if renounces are recorded
+        renounce: Option<pos::Team>,
+    },
+}
+
+/// What a single player is allowed to see of the game.
+///
+/// Other players' hands are never included, so this can be sent to clients as is.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PlayerView {
+    /// Player this view was made for.
+    pub pos: pos::PlayerPos,
+    /// Cards of this player.
+    pub hand: cards::Hand,
+    /// Cards currently on the table.
+    pub current_trick: trick::Trick,
+    /// Previous trick, if any.
+    pub last_trick: Option<trick::Trick>,
+    /// Contract being played.
+    pub contract: bid::Contract,
+    /// Points made so far by each team.
+    pub points: [i32; 2],
+    /// Player expected to play next.
+    pub next_player: pos::PlayerPos,
+    /// Cards this player may play now: empty when it is not their turn.
+    #[serde(default)]
+    pub legal_moves: cards::Hand,
+    /// Every completed trick, in order.
+    #[serde(default)]
+    pub tricks: Vec<trick::Trick>,
+    /// Rules used to play and score the game.
+    #[serde(default)]
+    pub rules: rules::RuleSet,
+}
+
+/// How much of the game spectators are allowed to see.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum RevealPolicy {
+    /// Hands are never shown. Cards are shown as soon as they are played.
+    #[default]
+    Hidden,
+    /// The view stays one trick behind: the last complete trick is shown on the table,
+    /// and the cards of the trick being played are not shown at all.
+    /// Hands are only shown once the game is over.
+    Delayed,
+}
+
+/// What spectators are allowed to see of the game, following a `RevealPolicy`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SpectatorView {
+    /// Hands of each player, if revealed.
+    pub hands: Option<[cards::Hand; 4]>,
+    /// Completed tricks.
+    pub tricks: Vec<trick::Trick>,
+    /// Cards on the table, if shown and the game is not over.
+    ///
+    /// With `RevealPolicy::Delayed`, this is the last complete trick.
+    pub current_trick: Option<trick::Trick>,
+    /// Contract being played.
+    pub contract: bid::Contract,
+    /// Points made by each team, as far as the view goes.
+    pub points: [i32; 2],
+    /// Player expected to play next, as far as the view goes.
+    pub next_player: pos::PlayerPos,
+}
+
+/// Serializable snapshot of a game in progress.
+///
+/// Use `GameState::snapshot` to take one, and `GameState::restore` to resume the game.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct GameSnapshot {
+    /// Cards still held by each player.
+    pub hands: [cards::Hand; 4],
+    /// Tricks played so far, including the current one.
+    pub tricks: Vec<trick::Trick>,
+    /// Contract being played.
+    pub contract: bid::Contract,
+    /// Player expected to play next.
+    pub current: pos::PlayerPos,
+    /// Points won so far by each team.
+    pub points: [i32; 2],
+    /// Rules used for this game.
+    #[serde(default)]
+    pub rules: rules::RuleSet,
+}
+
+/// A completed trick, with its outcome.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct TrickInfo {
+    /// Index of the trick in the deal, from 0 to 7.
+    pub index: usize,
+    /// Cards played in the trick.
+    pub trick: trick::Trick,
+    /// Player who won the trick.
+    pub winner: pos::PlayerPos,
+    /// Points of the trick, including the dix de der.
+    pub points: i32,
+}
+
+/// Changes between two states of the same deal, to bring one up to date with the other.
+///
+/// Use `GameState::diff` to compute one, and `GameState::apply_patch` to apply it.
+#[derive(Eq, PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct StatePatch {
+    /// Number of cards to take back first.
+    pub undo: usize,
+    /// Cards to play next, in order.
+    pub plays: Vec<(pos::PlayerPos, cards::Card)>,
+    /// Team who conceded the deal, if any.
+    pub conceded: Option<pos::Team>,
+    /// Points won by each team once the patch is applied.
+    pub points: [i32; 2],
+    /// Hash of the game once the patch is applied.
+    pub hash: u64,
+}
+
+/// Something that happened during the card play.
+///
+/// Events can be serialized, to send them to clients as they happen.
+#[derive(Eq, PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum GameEvent {
+    /// A player played a card.
+    CardPlayed {
+        /// Player who played the card.
+        pos: pos::PlayerPos,
+        /// Card played.
+        card: cards::Card,
+        /// Metadata attached to the card, if any.
+        metadata: Option<String>,
+    },
+    /// A trick was completed.
+    TrickWon {
+        /// Player who won the trick.
+        winner: pos::PlayerPos,
+        /// Points of the trick, including the dix de der.
+        points: i32,
+        /// Metadata attached to the trick, if any.
+        metadata: Option<String>,
+    },
+    /// The player holding the King and Queen of trump played one of them.
+    BeloteAnnounced {
+        /// Player holding the belote.
+        pos: pos::PlayerPos,
+        /// `true` for the second card ("rebelote").
+        rebelote: bool,
     },
+    /// The last trick was completed.
+    GameEnded(GameResult),
+}
+
+/// How `GameState::projected_result` splits the points not won yet.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Projection {
+    /// The remaining points are not counted.
+    Stop,
+    /// The remaining points are split according to the points won so far.
+    Proportional,
+}
+
+/// Teaching label given to a legal card by `GameState::move_tags`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum MoveTag {
+    /// The card would win the trick as it stands, though later players may still beat it.
+    Winning,
+    /// The card cannot win the trick: a card already played beats it.
+    Loser,
+    /// The card loses to an opponent who is winning the trick, giving them these points.
+    GivesPoints(i32),
+    /// The card is the highest trump left, played while the partner is already winning.
+    BurnsMasterTrump,
 }
 
 /// Result of a trick
@@ -48,376 +282,3590 @@ pub enum TrickResult {
 #[derive(Eq, PartialEq, Debug)]
 pub enum PlayError {
     /// A player tried to act before his turn
-    TurnError,
+    TurnError {
+        /// Player whose turn it actually is.
+        expected: pos::PlayerPos,
+    },
     /// A player tried to play a card he doesn't have
-    CardMissing,
+    CardMissing {
+        /// Card the player tried to play.
+        card: cards::Card,
+    },
     /// A player tried to play the wrong suit, while he still have some
-    IncorrectSuit,
+    IncorrectSuit {
+        /// Card the player tried to play.
+        card: cards::Card,
+        /// Suit that must be followed.
+        expected: cards::Suit,
+    },
     /// A player tried to play the wrong suit, while he still have trumps
-    InvalidPiss,
+    InvalidPiss {
+        /// Card the player tried to play.
+        card: cards::Card,
+        /// Trump suit the player must play.
+        trump: cards::Suit,
+    },
     /// A player did not raise on the last played trump
-    NonRaisedTrump,
+    NonRaisedTrump {
+        /// Card the player tried to play.
+        card: cards::Card,
+        /// Lowest rank that would raise.
+        minimum: cards::Rank,
+    },
 
     /// No last trick is available for display
     NoLastTrick,
+    /// A player claimed tricks that could still be lost
+    InvalidClaim,
+    /// A player tried to act after the end of the game
+    GameOver,
 }
 
 impl fmt::Display for PlayError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            PlayError::TurnError => write!(f, "invalid turn order"),
-            PlayError::CardMissing => write!(f, "you can only play cards you have"),
-            PlayError::IncorrectSuit => write!(f, "wrong suit played"),
-            PlayError::InvalidPiss => write!(f, "you must use trumps"),
-            PlayError::NonRaisedTrump => write!(f, "too weak trump played"),
+            PlayError::TurnError { expected } => {
+                write!(f, "invalid turn order: waiting for {}", expected)
+            }
+            PlayError::CardMissing { card } => {
+                write!(f, "you can only play cards you have: no {} in hand", card)
+            }
+            PlayError::IncorrectSuit { card, expected } => {
+                write!(f, "wrong suit played: {} instead of {}", card, expected)
+            }
+            PlayError::InvalidPiss { card, trump } => {
+                write!(f, "you must use trumps: {} instead of {}", card, trump)
+            }
+            PlayError::NonRaisedTrump { card, minimum } => write!(
+                f,
+                "too weak trump played: {} instead of at least {}",
+                card, minimum
+            ),
             PlayError::NoLastTrick => write!(f, "no trick has been played yet"),
+            PlayError::InvalidClaim => write!(f, "the claimed tricks are not guaranteed"),
+            PlayError::GameOver => write!(f, "the game is over"),
         }
     }
 }
 
 impl GameState {
     /// Creates a new GameState, with the given cards, first player and contract.
+    ///
+    /// The inputs are not checked: use `try_new` for untrusted deals.
     pub fn new(first: pos::PlayerPos, hands: [cards::Hand; 4], contract: bid::Contract) -> Self {
+        GameState::new_with_scoring(first, hands, contract, Arc::new(points::ClassicScoring))
+    }
+
+    /// Creates a new GameState, after checking the deal and the contract.
+    ///
+    /// Returns an error if the hands don't hold 8 distinct cards each,
+    /// or if the contract is inconsistent.
+    pub fn try_new(
+        first: pos::PlayerPos,
+        hands: [cards::Hand; 4],
+        contract: bid::Contract,
+    ) -> Result<Self, String> {
+        check_deal(&hands, &contract)?;
+        Ok(GameState::new(first, hands, contract))
+    }
+
+    /// Creates a new GameState, using the given scoring scheme to count points.
+    pub fn new_with_scoring(
+        first: pos::PlayerPos,
+        hands: [cards::Hand; 4],
+        contract: bid::Contract,
+        scoring: Arc<dyn points::ScoringScheme + Send + Sync>,
+    ) -> Self {
+        let belote = find_belote(&hands, contract.trump);
+        let hash = contract_key(&contract) ^ player_key(first);
         GameState {
             players: hands,
             current: first,
-            contract,
-            tricks: vec![trick::Trick::new(first)],
+            belote,
+            announces: None,
+            rules: rules::RuleSet::default(),
+            events: Vec::new(),
+            record_events: true,
+            hash,
+            conceded: None,
+            renounce: None,
+            metadata: Arc::new(Vec::new()),
+            contract: Arc::new(contract),
+            tricks: [trick::Trick::new(first); 8],
+            trick_count: 1,
             points: [0; 2],
+            scoring,
+        }
+    }
+
+    /// Rebuilds a game by playing the recorded cards, with the default rules.
+    ///
+    /// Every card is validated: returns the first error encountered.
+    pub fn replay(
+        first: pos::PlayerPos,
+        hands: [cards::Hand; 4],
+        contract: bid::Contract,
+        cards: &[(pos::PlayerPos, cards::Card)],
+    ) -> Result<Self, PlayError> {
+        GameState::replay_with_rules(first, hands, contract, rules::RuleSet::default(), cards)
+    }
+
+    /// Rebuilds a game by playing the recorded cards, with the given rules.
+    ///
+    /// Every card is validated: returns the first error encountered.
+    pub fn replay_with_rules(
+        first: pos::PlayerPos,
+        hands: [cards::Hand; 4],
+        contract: bid::Contract,
+        rules: rules::RuleSet,
+        cards: &[(pos::PlayerPos, cards::Card)],
+    ) -> Result<Self, PlayError> {
+        let mut game = GameState::new(first, hands, contract).with_rules(rules);
+        for &(pos, card) in cards {
+            game.play_card(pos, card)?;
+        }
+        game.events.clear();
+        Ok(game)
+    }
+
+    /// Sets the rules used to play and score this game.
+    pub fn with_rules(mut self, rules: rules::RuleSet) -> Self {
+        self.rules = rules;
+        self.with_announces(rules.announces)
+    }
+
+    /// Returns the rules used by this game.
+    pub fn rules(&self) -> rules::RuleSet {
+        self.rules
+    }
+
+    /// Enables declarations (announces): sequences and carrés found in the dealt hands.
+    ///
+    /// Not all tables play with announces, so they are disabled by default.
+    pub fn with_announces(mut self, enabled: bool) -> Self {
+        self.rules.announces = enabled;
+        self.announces = if enabled {
+            let mut found: [Vec<announces::Announce>; 4] = Default::default();
+            for (announces, &hand) in found.iter_mut().zip(self.players.iter()) {
+                *announces = announces::find_announces(hand);
+            }
+            Some(Arc::new(found))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Returns the declarations of each player.
+    ///
+    /// Declarations are only revealed once the first trick is over,
+    /// and only if announces were enabled.
+    pub fn announces(&self) -> Option<&[Vec<announces::Announce>; 4]> {
+        if self.trick_count > 1 || self.is_over() {
+            self.announces.as_deref()
+        } else {
+            None
         }
     }
 
+    /// Returns the player holding the belote (King and Queen of trump), if any.
+    pub fn belote(&self) -> Option<pos::PlayerPos> {
+        self.belote
+    }
+
     /// Returns the contract used for this game
     pub fn contract(&self) -> &bid::Contract {
         &self.contract
     }
 
+    /// Returns what `pos` is allowed to see of the game.
+    pub fn view_for(&self, pos: pos::PlayerPos) -> PlayerView {
+        PlayerView {
+            pos,
+            hand: self.players[pos as usize],
+            current_trick: *self.current_trick(),
+            last_trick: self.last_trick().ok().copied(),
+            contract: (*self.contract).clone(),
+            points: self.points,
+            next_player: self.current,
+            legal_moves: self.legal_moves(pos).unwrap_or_default(),
+            tricks: self.tricks().to_vec(),
+            rules: self.rules,
+        }
+    }
+
+    /// Returns what spectators are allowed to see of the game, with the given policy.
+    pub fn spectator_view(&self, policy: RevealPolicy) -> SpectatorView {
+        let over = self.is_over();
+        let tricks = self.tricks();
+        let mut points = self.points;
+        let (hands, tricks, current_trick, next_player) = match policy {
+            RevealPolicy::Hidden => {
+                let trick = (!over).then_some(*self.current_trick());
+                (None, tricks, trick, self.current)
+            }
+            RevealPolicy::Delayed if over => (Some(self.players), tricks, None, self.current),
+            RevealPolicy::Delayed => match tricks.split_last() {
+                // Stay one trick behind: the last complete trick is still on the table.
+                Some((last, rest)) => {
+                    let trump = self.contract.trump;
+                    points[last.winner.team() as usize] -= last.score_with(trump, &*self.scoring);
+                    (None, rest, Some(*last), last.winner)
+                }
+                None => (None, tricks, None, self.current_trick().first),
+            },
+        };
+
+        SpectatorView {
+            hands,
+            tricks: tricks.to_vec(),
+            current_trick,
+            contract: (*self.contract).clone(),
+            points,
+            next_player,
+        }
+    }
+
+    /// Returns the cards `player` may play right now.
+    pub fn legal_moves(&self, player: pos::PlayerPos) -> Result<cards::Hand, PlayError> {
+        if self.conceded.is_some() {
+            return Err(PlayError::GameOver);
+        }
+        if self.current != player {
+            return Err(PlayError::TurnError {
+                expected: self.current,
+            });
+        }
+
+        Ok(legal_cards_with(
+            player,
+            self.players[player as usize],
+            self.current_trick(),
+            self.contract.trump,
+            &self.rules,
+        ))
+    }
+
+    /// Returns each card in the hand of `player`, with the reason it cannot be played, if any.
+    ///
+    /// If it is not the turn of `player`, every card is marked with `PlayError::TurnError`.
+    pub fn annotate_moves(
+        &self,
+        player: pos::PlayerPos,
+    ) -> Vec<(cards::Card, Result<(), PlayError>)> {
+        let hand = self.players[player as usize];
+        hand.list()
+            .into_iter()
+            .map(|card| {
+                let result = if self.current != player {
+                    Err(PlayError::TurnError {
+                        expected: self.current,
+                    })
+                } else {
+                    can_play_with(
+                        player,
+                        card,
+                        hand,
+                        self.current_trick(),
+                        self.contract.trump,
+                        &self.rules,
+                    )
+                };
+                (card, result)
+            })
+            .collect()
+    }
+
+    /// Returns each legal card of `player`, with teaching tags a client can show to beginners.
+    ///
+    /// Tags only rely on what `player` can see: their own hand and the cards played so far.
+    pub fn move_tags(
+        &self,
+        player: pos::PlayerPos,
+    ) -> Result<Vec<(cards::Card, Vec<MoveTag>)>, PlayError> {
+        let legal = self.legal_moves(player)?;
+        let trump = self.contract.trump;
+        let trick = self.current_trick();
+        let partner_winning = !trick.is_empty() && player.is_partner(trick.winner);
+
+        let seen = self.tracker().seen();
+        let master_trump = match trump {
+            cards::Trump::Suit(suit) => cards::Rank::by_trump_strength()
+                .rev()
+                .map(|rank| cards::Card::new(suit, rank))
+                .find(|&card| !seen.has(card)),
+            _ => None,
+        };
+
+        Ok(legal
+            .list()
+            .into_iter()
+            .map(|card| {
+                let mut after = *trick;
+                after.play_card(player, card, trump);
+
+                let mut tags = Vec::new();
+                if after.winner == player {
+                    tags.push(MoveTag::Winning);
+                } else {
+                    tags.push(MoveTag::Loser);
+                    let points = self.scoring.score(card, trump);
+                    if !player.is_partner(after.winner) && points > 0 {
+                        tags.push(MoveTag::GivesPoints(points));
+                    }
+                }
+                if partner_winning && master_trump == Some(card) {
+                    tags.push(MoveTag::BurnsMasterTrump);
+                }
+                (card, tags)
+            })
+            .collect())
+    }
+
+    /// Suggests a card for `player` to play, using a simple greedy heuristic.
+    ///
+    /// * When leading, plays a plain ace if possible, or the cheapest card.
+    /// * When the partner is winning, plays the cheapest card.
+    /// * Otherwise, wins the trick with the cheapest winning card, or plays the cheapest card.
+    ///
+    /// Trumps are considered more expensive than any plain card, so they are kept when possible.
+    /// The suggested card is always legal.
+    pub fn hint(&self, player: pos::PlayerPos) -> Result<cards::Card, PlayError> {
+        let legal = self.legal_moves(player)?.list();
+        let trump = self.contract.trump;
+        let trick = self.current_trick();
+
+        let cost = |card: &cards::Card| {
+            (
+                trump.is_trump(card.suit()),
+                points::score(*card, trump),
+                points::strength(*card, trump),
+            )
+        };
+        let cheapest = legal.iter().copied().min_by_key(cost).unwrap();
+
+        let winning = match trick.cards[trick.winner as usize] {
+            None => {
+                let ace = legal
+                    .iter()
+                    .copied()
+                    .find(|c| c.rank() == cards::Rank::RankA && !trump.is_trump(c.suit()));
+                return Ok(ace.unwrap_or(cheapest));
+            }
+            Some(_) if player.is_partner(trick.winner) => return Ok(cheapest),
+            Some(card) => card,
+        };
+
+        Ok(legal
+            .iter()
+            .copied()
+            .filter(|&c| points::beats(c, winning, trump))
+            .min_by_key(cost)
+            .unwrap_or(cheapest))
+    }
+
+    /// Plays the card of each player who has a single legal card, until someone has a choice.
+    ///
+    /// Returns the cards played, in order.
+    pub fn play_forced(&mut self) -> Vec<(pos::PlayerPos, cards::Card)> {
+        let mut played = Vec::new();
+        while !self.is_over() {
+            let player = self.current;
+            let legal = self.legal_moves(player).unwrap();
+            if legal.size() != 1 {
+                break;
+            }
+            let card = legal.get_card();
+            self.play_card(player, card).unwrap();
+            played.push((player, card));
+        }
+        played
+    }
+
+    /// Plays uniformly random legal cards until the end of the deal.
+    ///
+    /// Returns the result of the game (also when it was already over).
+    pub fn playout<R: Rng + ?Sized>(&mut self, rng: &mut R) -> GameResult {
+        while !self.is_over() {
+            let player = self.current;
+            let card = self
+                .legal_moves(player)
+                .unwrap()
+                .pick_random_n(rng, 1)
+                .get_card();
+            self.play_inner(player, card, None).unwrap();
+        }
+        self.get_game_result()
+    }
+
+    /// Plays a copy of this game to the end with random legal cards, leaving this game untouched.
+    ///
+    /// See `playout`.
+    pub fn playout_cloned<R: Rng + ?Sized>(&self, rng: &mut R) -> GameResult {
+        self.clone().playout(rng)
+    }
+
     /// Try to play a card
     pub fn play_card(
         &mut self,
         player: pos::PlayerPos,
         card: cards::Card,
     ) -> Result<TrickResult, PlayError> {
+        self.play(player, card, None)
+    }
+
+    /// Try to play a card, attaching an opaque payload to it (a timestamp, a message id...).
+    ///
+    /// The payload is carried by the `CardPlayed` event, and returned by `card_metadata`.
+    pub fn play_card_with_metadata(
+        &mut self,
+        player: pos::PlayerPos,
+        card: cards::Card,
+        metadata: String,
+    ) -> Result<TrickResult, PlayError> {
+        self.play(player, card, Some(metadata))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, metadata),
+            fields(player = %player, card = %card, contract = %self.contract),
+            err(Display)
+        )
+    )]
+    fn play(
+        &mut self,
+        player: pos::PlayerPos,
+        card: cards::Card,
+        metadata: Option<String>,
+    ) -> Result<TrickResult, PlayError> {
+        #[cfg(feature = "tracing")]
+        let before = self.points;
+        let result = self.play_inner(player, card, metadata)?;
+        #[cfg(feature = "tracing")]
+        if let TrickResult::TrickOver(winner, ref game_result) = result {
+            let team = winner.team() as usize;
+            let trick = self.tricks().len() - 1;
+            let points = self.points[team] - before[team];
+            trace_event!(trick, winner = %winner, points, "trick won");
+            trace_result(&self.contract, game_result);
+        }
+        Ok(result)
+    }
+
+    // Plays a card without tracing, for searches that play many hypothetical cards.
+    pub(crate) fn play_inner(
+        &mut self,
+        player: pos::PlayerPos,
+        card: cards::Card,
+        metadata: Option<String>,
+    ) -> Result<TrickResult, PlayError> {
+        if self.conceded.is_some() {
+            return Err(PlayError::GameOver);
+        }
         if self.current != player {
-            return Err(PlayError::TurnError);
+            return Err(PlayError::TurnError {
+                expected: self.current,
+            });
         }
 
         // Is that a valid move?
-        can_play(
+        let legality = can_play_with(
             player,
             card,
             self.players[player as usize],
             self.current_trick(),
             self.contract.trump,
-        )?;
+            &self.rules,
+        );
+        match legality {
+            Ok(()) => (),
+            // The card is on the table anyway: remember who is to blame.
+            Err(PlayError::IncorrectSuit { .. })
+            | Err(PlayError::InvalidPiss { .. })
+            | Err(PlayError::NonRaisedTrump { .. })
+                if self.rules.record_renounces =>
+            {
+                if self.renounce.is_none() {
+                    self.renounce = Some((player, card));
+                }
+            }
+            Err(err) => return Err(err),
+        }
 
         // Play the card
         let trump = self.contract.trump;
+        let index = self.trick_count - 1;
+        self.players[player as usize].remove(card);
         let trick_over = self.current_trick_mut().play_card(player, card, trump);
+        if let Some(ref metadata) = metadata {
+            Arc::make_mut(&mut self.metadata).push((index, Some(player), metadata.clone()));
+        }
+        self.push_event(GameEvent::CardPlayed {
+            pos: player,
+            card,
+            metadata,
+        });
+        if self.belote == Some(player) && self.is_belote_card(card) {
+            let rebelote = !self.is_belote_card_held(player);
+            self.push_event(GameEvent::BeloteAnnounced {
+                pos: player,
+                rebelote,
+            });
+        }
 
         // Is the trick over?
         let result = if trick_over {
             let winner = self.current_trick().winner;
-            let score = self.current_trick().score(trump);
-            self.points[winner.team() as usize] += score;
-            if self.tricks.len() == 8 {
-                // 10 de der
-                self.points[winner.team() as usize] += 10;
+            let mut score = self.current_trick().score_with(trump, &*self.scoring);
+            if self.trick_count == 8 {
+                score += self.dix_de_der(winner.team());
             } else {
-                self.tricks.push(trick::Trick::new(winner));
+                self.tricks[self.trick_count] = trick::Trick::new(winner);
+                self.trick_count += 1;
             }
+            self.points[winner.team() as usize] += score;
             self.current = winner;
-            TrickResult::TrickOver(winner, self.get_game_result())
+            let metadata = self.trick_metadata(index).map(str::to_string);
+            self.push_event(GameEvent::TrickWon {
+                winner,
+                points: score,
+                metadata,
+            });
+
+            let result = self.get_game_result();
+            if result != GameResult::Nothing && self.record_events {
+                self.events.push(GameEvent::GameEnded(result.clone()));
+            }
+            TrickResult::TrickOver(winner, result)
         } else {
             self.current = self.current.next();
             TrickResult::Nothing
         };
+        self.hash ^= card_key(player, card) ^ player_key(player) ^ player_key(self.current);
+
+        // Checking on every card is too slow for searches, even in debug builds.
+        if cfg!(test) {
+            if let Err(err) = self.check_invariants() {
+                panic!("corrupted game state: {}", err);
+            }
+        }
 
         Ok(result)
     }
 
-    /// Returns the player expected to play next.
-    pub fn next_player(&self) -> pos::PlayerPos {
-        self.current
+    /// Enables or disables event recording (enabled by default).
+    ///
+    /// Without events, cloning and playing cards never allocates,
+    /// which makes game-tree searches faster.
+    pub fn with_events(mut self, enabled: bool) -> Self {
+        self.record_events = enabled;
+        if !enabled {
+            self.events = Vec::new();
+        }
+        self
     }
 
-    fn get_game_result(&self) -> GameResult {
-        if !self.is_over() {
-            return GameResult::Nothing;
+    // Removes the events emitted since `player` played `card`, if they were not drained.
+    fn pop_events(&mut self, player: pos::PlayerPos, card: cards::Card) {
+        while let Some(event) = self.events.pop() {
+            if let GameEvent::CardPlayed {
+                pos, card: played, ..
+            } = event
+            {
+                if (pos, played) != (player, card) {
+                    self.events.push(event);
+                }
+                break;
+            }
+        }
+    }
+
+    fn push_event(&mut self, event: GameEvent) {
+        if self.record_events {
+            self.events.push(event);
+        }
+    }
+
+    /// Claims that the team of `player` will win `n_tricks` of the remaining tricks, whatever happens.
+    ///
+    /// The claim is checked by the double-dummy solver, with all hands revealed,
+    /// so it is only accepted once at most `MAX_CLAIM_TRICKS` tricks remain.
+    /// If the claim holds, the rest of the deal is played out: the claiming team follows
+    /// a line that secures its tricks, and the opponents play the `hint` card.
+    ///
+    /// Returns `PlayError::InvalidClaim` if the opponents could prevent it, or if it is too early.
+    pub fn claim(
+        &mut self,
+        player: pos::PlayerPos,
+        n_tricks: usize,
+    ) -> Result<GameResult, PlayError> {
+        if self.conceded.is_some() || self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+        if self.tricks_remaining() > MAX_CLAIM_TRICKS {
+            return Err(PlayError::InvalidClaim);
+        }
+
+        let team = player.team();
+        let won = |game: &GameState| {
+            game.tricks()
+                .iter()
+                .filter(|trick| trick.winner.team() == team)
+                .count()
+        };
+        let mut solver = solver::Solver::with_objective(solver::Objective::Tricks);
+        let solution = solver.solve(self);
+        if solution.tricks[team as usize] < won(self) + n_tricks {
+            return Err(PlayError::InvalidClaim);
+        }
+
+        while !self.is_over() {
+            let current = self.current;
+            let card = if current.team() == team {
+                solver
+                    .evaluate(self)
+                    .into_iter()
+                    .max_by_key(|&(_, tricks)| tricks)
+                    .unwrap()
+                    .0
+            } else {
+                self.hint(current)?
+            };
+            self.play_card(current, card)?;
+        }
+
+        Ok(self.get_game_result())
+    }
+
+    /// Returns the events that happened since the last call, and clears them.
+    ///
+    /// Servers can use this after each card to broadcast the changes.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn is_belote_card(&self, card: cards::Card) -> bool {
+        Some(card.suit()) == self.contract.trump.suit()
+            && (card.rank() == cards::Rank::RankK || card.rank() == cards::Rank::RankQ)
+    }
+
+    // Returns `true` if `player` still holds the King or Queen of trump.
+    fn is_belote_card_held(&self, player: pos::PlayerPos) -> bool {
+        self.players[player as usize]
+            .list()
+            .into_iter()
+            .any(|card| self.is_belote_card(card))
+    }
+
+    /// Takes back the last card played.
+    ///
+    /// If that card had closed a trick, the trick is reopened and its points removed.
+    /// A concession is cancelled along with the card, and the events they emitted are
+    /// removed unless they were already drained.
+    ///
+    /// Returns the player and the card taken back, or `None` if no card was played yet.
+    pub fn undo(&mut self) -> Option<(pos::PlayerPos, cards::Card)> {
+        let trump = self.contract.trump;
+
+        // The last card may have closed the previous trick.
+        if self.trick_count > 1 && self.current_trick().is_empty() {
+            self.trick_count -= 1;
+        }
+
+        let trick = self.current_trick();
+        let player = if trick.len() == 4 {
+            // Remove the trick points before the winner changes.
+            let winner = trick.winner.team() as usize;
+            let mut score = trick.score_with(trump, &*self.scoring);
+            if self.trick_count == 8 {
+                score += self.dix_de_der(trick.winner.team());
+            }
+            let player = trick.first.prev();
+            self.points[winner] -= score;
+            player
+        } else {
+            self.current.prev()
+        };
+
+        let card = self.current_trick_mut().take_back(player, trump)?;
+        self.players[player as usize].add(card);
+        let index = self.trick_count - 1;
+        Arc::make_mut(&mut self.metadata).retain(|&(i, p, _)| (i, p) != (index, Some(player)));
+        if self.renounce == Some((player, card)) {
+            self.renounce = None;
+        }
+        self.hash ^= card_key(player, card) ^ player_key(self.current) ^ player_key(player);
+        self.current = player;
+        self.conceded = None;
+        self.pop_events(player, card);
+
+        if cfg!(test) {
+            if let Err(err) = self.check_invariants() {
+                panic!("corrupted game state: {}", err);
+            }
+        }
+
+        Some((player, card))
+    }
+
+    /// Takes a snapshot of the game, to be serialized and restored later.
+    ///
+    /// The scoring scheme is not included.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            hands: self.players,
+            tricks: self.started_tricks().to_vec(),
+            contract: (*self.contract).clone(),
+            current: self.current,
+            points: self.points,
+            rules: self.rules,
+        }
+    }
+
+    /// Resumes a game from a snapshot, with the classic scoring.
+    ///
+    /// See `restore_with_scoring`.
+    pub fn restore(snapshot: GameSnapshot) -> Result<Self, String> {
+        GameState::restore_with_scoring(snapshot, Arc::new(points::ClassicScoring))
+    }
+
+    /// Resumes a game from a snapshot, with the given scoring scheme.
+    ///
+    /// The snapshot is validated by replaying every card from the dealt hands:
+    /// returns an error if any card was illegal, or if the turn, winners or points don't match.
+    pub fn restore_with_scoring(
+        snapshot: GameSnapshot,
+        scoring: Arc<dyn points::ScoringScheme + Send + Sync>,
+    ) -> Result<Self, String> {
+        let first = snapshot.tricks.first().ok_or("no trick in snapshot")?.first;
+
+        // Rebuild the dealt hands.
+        let mut hands = snapshot.hands;
+        for trick in &snapshot.tricks {
+            for p in pos::PlayerPos::all() {
+                if let Some(card) = trick.cards[p as usize] {
+                    hands[p as usize].add(card);
+                }
+            }
+        }
+        check_deal(&hands, &snapshot.contract)?;
+
+        let mut game = GameState::new_with_scoring(first, hands, snapshot.contract, scoring)
+            .with_rules(snapshot.rules);
+        for trick in &snapshot.tricks {
+            for (p, card) in trick.iter_in_play_order() {
+                game.play_card(p, card)
+                    .map_err(|err| format!("cannot play {} for {}: {}", card, p, err))?;
+            }
+        }
+
+        if game.players != snapshot.hands || game.trick_count != snapshot.tricks.len() {
+            return Err("tricks do not match the hands".to_string());
+        }
+        if game.current != snapshot.current {
+            return Err(format!(
+                "expected {} to play, not {}",
+                game.current, snapshot.current
+            ));
+        }
+        if game.points != snapshot.points {
+            return Err(format!(
+                "points {:?} do not match tricks {:?}",
+                snapshot.points, game.points
+            ));
+        }
+
+        game.events.clear();
+        Ok(game)
+    }
+
+    /// Returns the changes needed to bring `older` up to date with this game.
+    ///
+    /// Both games should come from the same deal: `older` may be behind,
+    /// or may have cards taken back since.
+    pub fn diff(&self, older: &GameState) -> StatePatch {
+        let plays = self.played_cards();
+        let old_plays = older.played_cards();
+        let common = plays
+            .iter()
+            .zip(old_plays.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        StatePatch {
+            undo: old_plays.len() - common,
+            plays: plays[common..].to_vec(),
+            conceded: self.conceded,
+            points: self.points,
+            hash: self.hash,
+        }
+    }
+
+    /// Applies a patch computed by `diff`.
+    ///
+    /// Returns an error if a card cannot be played, or if the result does not match the patch.
+    /// The game is left untouched on error.
+    pub fn apply_patch(&mut self, patch: &StatePatch) -> Result<(), String> {
+        let mut game = self.clone();
+        for _ in 0..patch.undo {
+            game.undo().ok_or("no card to take back")?;
+        }
+        for &(p, card) in &patch.plays {
+            game.play_card(p, card)
+                .map_err(|err| format!("cannot play {} for {}: {}", card, p, err))?;
+        }
+        if let Some(team) = patch.conceded.filter(|_| game.conceded.is_none()) {
+            game.concede(team)
+                .map_err(|err| format!("cannot concede for {}: {}", team, err))?;
+        }
+
+        if game.conceded != patch.conceded {
+            return Err("concession does not match the patch".to_string());
+        }
+        if game.points != patch.points {
+            return Err(format!(
+                "points {:?} do not match the patch {:?}",
+                game.points, patch.points
+            ));
+        }
+        if game.hash != patch.hash {
+            return Err("hash does not match the patch".to_string());
+        }
+
+        *self = game;
+        Ok(())
+    }
+
+    // Returns every card played so far, in order.
+    fn played_cards(&self) -> Vec<(pos::PlayerPos, cards::Card)> {
+        self.started_tricks()
+            .iter()
+            .flat_map(trick::Trick::iter_in_play_order)
+            .collect()
+    }
+
+    /// Checks that the game state is consistent:
+    ///
+    /// * Every card is either in a hand or on the table, exactly once.
+    /// * Each player holds one card per trick not yet played.
+    /// * Points match the tricks won so far.
+    ///
+    /// Games built with `GameStateBuilder` are checked once; playing and undoing cards
+    /// is only checked by the crate's own tests.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut seen = cards::Hand::new();
+        let mut count = 0;
+
+        let played = self
+            .started_tricks()
+            .iter()
+            .flat_map(|trick| trick.cards.iter().filter_map(|&c| c));
+        let held = self.players.iter().flat_map(|hand| hand.list());
+        for card in played.chain(held) {
+            if seen.has(card) {
+                return Err(format!("card {} appears twice", card));
+            }
+            seen.add(card);
+            count += 1;
+        }
+        if count != 32 {
+            return Err(format!("found {} cards instead of 32", count));
+        }
+
+        let trick = self.current_trick();
+        for p in pos::PlayerPos::all() {
+            let played = if trick.cards[p as usize].is_some() {
+                1
+            } else {
+                0
+            };
+            let expected = 9 - self.trick_count - played;
+            let size = self.players[p as usize].size();
+            if size != expected {
+                return Err(format!(
+                    "{:?} has {} cards instead of {}",
+                    p, size, expected
+                ));
+            }
+        }
+
+        let trump = self.contract.trump;
+        let mut total: i32 = self
+            .started_tricks()
+            .iter()
+            .map(|trick| trick.score_with(trump, &*self.scoring))
+            .sum();
+        if self.all_tricks_played() {
+            total += self.dix_de_der(trick.winner.team());
+        } else {
+            // The current trick is not won yet.
+            total -= trick.score_with(trump, &*self.scoring);
+        }
+        if self.points[0] + self.points[1] != total {
+            return Err(format!(
+                "points {:?} do not match tricks total {}",
+                self.points, total
+            ));
+        }
+
+        if self.hash != self.full_hash() {
+            return Err("hash does not match the cards played".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Returns a 64-bit Zobrist hash of the game.
+    ///
+    /// It depends on the cards played by each player, the player expected to play next,
+    /// and the contract. It is updated with each card, so it is cheap to call,
+    /// for instance to fill transposition tables.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    // Computes the hash from scratch.
+    fn full_hash(&self) -> u64 {
+        let mut hash = contract_key(&self.contract) ^ player_key(self.current);
+        for trick in self.started_tricks() {
+            for p in pos::PlayerPos::all() {
+                if let Some(card) = trick.cards[p as usize] {
+                    hash ^= card_key(p, card);
+                }
+            }
+        }
+        hash
+    }
+
+    /// Returns a digest of the public state of the game, to detect desyncs.
+    ///
+    /// It depends on the contract, every card played in order, the player expected to play next,
+    /// the points and any concession, but not on the hands. Unlike the `HashMap` hasher,
+    /// it is stable across runs and platforms.
+    pub fn checksum(&self) -> u64 {
+        let conceded = self.conceded.map_or(0, |team| team as u64 + 1);
+        let values = [
+            contract_key(&self.contract),
+            player_key(self.current),
+            self.points[0] as u32 as u64,
+            self.points[1] as u32 as u64,
+            conceded,
+        ];
+        let plays = self
+            .played_cards()
+            .into_iter()
+            .map(|(p, card)| card_key(p, card));
+        checksum(values.iter().copied().chain(plays))
+    }
+
+    /// Returns the players who still have to play in the current trick, in play order.
+    ///
+    /// Returns an empty list once the game is over.
+    pub fn remaining_players(&self) -> Vec<pos::PlayerPos> {
+        self.current_trick().remaining_players()
+    }
+
+    /// Returns a tracker of the cards played so far, including the current trick.
+    pub fn tracker(&self) -> tracking::Tracker {
+        tracking::Tracker::from_tricks(self.started_tricks(), self.contract.trump, self.rules)
+    }
+
+    /// Returns what `seat` can deduce about the other hands from the cards played so far.
+    pub fn knowledge(&self, seat: pos::PlayerPos) -> tracking::Knowledge {
+        tracking::Knowledge::from_tricks(
+            seat,
+            self.players[seat as usize],
+            self.started_tricks(),
+            self.contract.trump,
+            self.rules,
+        )
+    }
+
+    /// Returns the first illegal card played and its player, if renounces are recorded.
+    pub fn renounce(&self) -> Option<(pos::PlayerPos, cards::Card)> {
+        self.renounce
+    }
+
+    /// Returns the player expected to play next.
+    pub fn next_player(&self) -> pos::PlayerPos {
+        self.current
+    }
+
+    fn get_game_result(&self) -> GameResult {
+        if let Some(team) = self.conceded {
+            // The other team gets every point not won yet.
+            let mut points = self.points;
+            points[team.opponent() as usize] += self.remaining_points();
+            return self.compute_result(points);
+        }
+        if !self.is_over() {
+            return GameResult::Nothing;
+        }
+
+        self.compute_result(self.points)
+    }
+
+    /// Ends the deal immediately, with `team` conceding it.
+    ///
+    /// The other team wins the deal: it scores the contract if it took it,
+    /// or the failure score otherwise, as set by the rules.
+    /// It also gets every point not won yet. No card can be played afterwards.
+    ///
+    /// Returns `PlayError::GameOver` if the game is already over.
+    pub fn concede(&mut self, team: pos::Team) -> Result<GameResult, PlayError> {
+        if self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+
+        self.conceded = Some(team);
+        let result = self.get_game_result();
+        trace_event!(team = %team, "deal conceded");
+        trace_result(&self.contract, &result);
+        self.push_event(GameEvent::GameEnded(result.clone()));
+        Ok(result)
+    }
+
+    /// Returns the result the game would have if it stopped now.
+    ///
+    /// The points not won yet are split according to `projection`.
+    /// Capot bonuses are only counted once the game is over.
+    pub fn projected_result(&self, projection: Projection) -> GameResult {
+        let mut points = self.points;
+        if projection == Projection::Proportional {
+            let remaining = self.remaining_points();
+            let won = points[0] + points[1];
+            let share = if won == 0 {
+                remaining / 2
+            } else {
+                remaining * points[0] / won
+            };
+            points[0] += share;
+            points[1] += remaining - share;
+        }
+        self.compute_result(points)
+    }
+
+    // Scores the game with the given trick points for each team.
+    fn compute_result(&self, points: [i32; 2]) -> GameResult {
+        // No-trump and all-trump deals may need to be brought back to the usual scale.
+        let deal_total = self.deal_total();
+        let normalization = self.scoring.normalization();
+        let points = [
+            points::normalize(points[0], deal_total, normalization),
+            points::normalize(points[1], deal_total, normalization),
+        ];
+
+        let taking_team = self.contract.author.team();
+        let belote = self.belote.map(pos::PlayerPos::team);
+        let mut taking_points = points[taking_team as usize];
+        // The belote counts towards the contract.
+        if belote == Some(taking_team) {
+            taking_points += points::BELOTE_SCORE;
+        }
+        // So do declarations.
+        let declarations = match self.announces {
+            Some(ref found) => announces::scores(found, self.tricks[0].first, self.contract.trump),
+            None => [0; 2],
+        };
+        taking_points += declarations[taking_team as usize];
+
+        let over = self.all_tricks_played();
+        let capot = over && self.is_capot(taking_team);
+        let generale = over && self.is_generale(self.contract.author);
+
+        let renounce = self.renounce.map(|(player, _)| player.team());
+        let victory = match (self.conceded, renounce) {
+            (Some(team), _) | (None, Some(team)) => team != taking_team,
+            (None, None) => self.contract.target.victory(taking_points, capot, generale),
+        };
+
+        let winners = if victory {
+            taking_team
+        } else {
+            taking_team.opponent()
+        };
+
+        let mut scores = [0; 2];
+        if victory {
+            scores[winners as usize] = self.contract.target.score();
+        } else {
+            scores[winners as usize] = match self.rules.failure {
+                rules::FailureScoring::Flat => rules::FAILURE_SCORE,
+                rules::FailureScoring::FlatPlusContract => {
+                    rules::FAILURE_SCORE + self.contract.target.score()
+                }
+            };
+        }
+        // Coinches raise the stakes for both teams.
+        scores[winners as usize] *= self.contract.multiplier();
+        // On success, each team may also keep the points it made.
+        if victory && self.rules.success == rules::SuccessScoring::ContractPlusPoints {
+            let rounding = self.scoring.rounding();
+            scores[0] += points::round_score(points[0], rounding);
+            scores[1] += points::round_score(points[1], rounding);
+        }
+        // Winning every trick earns a bonus, unless it was the announced contract.
+        let capot_team = pos::Team::all()
+            .iter()
+            .cloned()
+            .find(|&team| over && self.is_capot(team));
+        if let Some(team) = capot_team {
+            let announced = team == taking_team && self.contract.capot_announced();
+            if !announced {
+                scores[team as usize] += bid::CAPOT_BONUS;
+            }
+        }
+        // The belote is always kept by its team, even on a lost contract.
+        if let Some(team) = belote {
+            scores[team as usize] += points::BELOTE_SCORE;
+        }
+        scores[0] += declarations[0];
+        scores[1] += declarations[1];
+        // A renonce loses everything, belote included.
+        if let Some(team) = renounce {
+            scores[team as usize] = 0;
+        }
+
+        GameResult::GameOver {
+            points,
+            winners,
+            scores,
+            belote,
+            declarations,
+            contract: (*self.contract).clone(),
+            fulfilled: victory,
+            margin: match self.contract.target {
+                bid::Target::ContractCapot | bid::Target::Generale => None,
+                target => Some(taking_points - target.score()),
+            },
+            last_trick: self.current_trick().winner.team(),
+            dix_de_der: Some(self.current_trick().winner.team())
+                .filter(|&team| over && self.dix_de_der(team) != 0),
+            capot: capot_team,
+            multiplier: self.contract.multiplier(),
+            conceded: self.conceded,
+            renounce,
+        }
+    }
+
+    fn is_capot(&self, team: pos::Team) -> bool {
+        for trick in self.started_tricks() {
+            if trick.winner.team() != team {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn is_generale(&self, player: pos::PlayerPos) -> bool {
+        self.started_tricks()
+            .iter()
+            .all(|trick| trick.winner == player)
+    }
+
+    /// Returns the cards of all players
+    pub fn hands(&self) -> [cards::Hand; 4] {
+        self.players
+    }
+
+    /// Returns `true` once the last trick is complete, or if the deal was conceded.
+    pub fn is_over(&self) -> bool {
+        self.conceded.is_some() || self.all_tricks_played()
+    }
+
+    fn all_tricks_played(&self) -> bool {
+        self.trick_count == 8 && self.current_trick().is_complete()
+    }
+
+    /// Returns the number of the current trick, from 1 to 8.
+    ///
+    /// Stays at 8 once the game is over.
+    pub fn trick_number(&self) -> usize {
+        self.trick_count
+    }
+
+    /// Returns the number of tricks not completed yet, including the current one.
+    pub fn tricks_remaining(&self) -> usize {
+        8 - self.tricks().len()
+    }
+
+    /// Return the last trick, if possible
+    pub fn last_trick(&self) -> Result<&trick::Trick, PlayError> {
+        if self.trick_count == 1 {
+            Err(PlayError::NoLastTrick)
+        } else {
+            Ok(&self.tricks[self.trick_count - 2])
+        }
+    }
+
+    /// Returns the last completed trick, with its winner and points.
+    ///
+    /// Unlike `last_trick`, this includes the final trick once the game is over.
+    pub fn last_trick_info(&self) -> Result<TrickInfo, PlayError> {
+        let index = self
+            .tricks()
+            .len()
+            .checked_sub(1)
+            .ok_or(PlayError::NoLastTrick)?;
+        let trick = self.tricks()[index];
+        Ok(TrickInfo {
+            index,
+            trick,
+            winner: trick.winner,
+            points: self.trick_points(index).unwrap(),
+        })
+    }
+
+    /// Returns the points won so far by each team, including the dix de der.
+    ///
+    /// Cards still on the table are not counted yet.
+    pub fn points(&self) -> [i32; 2] {
+        self.points
+    }
+
+    /// Returns the points won so far by the given team.
+    pub fn team_points(&self, team: pos::Team) -> i32 {
+        self.points[team as usize]
+    }
+
+    /// Returns the points not won by any team yet, including the cards on the table.
+    pub fn remaining_points(&self) -> i32 {
+        self.deal_total() - self.points[0] - self.points[1]
+    }
+
+    /// Returns every completed trick, in order.
+    ///
+    /// The trick being played is not included (see `current_trick`).
+    pub fn tricks(&self) -> &[trick::Trick] {
+        if self.all_tricks_played() {
+            self.started_tricks()
+        } else {
+            &self.tricks[..self.trick_count - 1]
+        }
+    }
+
+    /// Attaches an opaque payload to the current trick, replacing any previous one.
+    ///
+    /// The payload is carried by the `TrickWon` event, and returned by `trick_metadata`.
+    pub fn set_trick_metadata(&mut self, metadata: String) {
+        let index = self.trick_count - 1;
+        Arc::make_mut(&mut self.metadata).retain(|&(i, p, _)| (i, p) != (index, None));
+        Arc::make_mut(&mut self.metadata).push((index, None, metadata));
+    }
+
+    /// Returns the payload attached to the `i`-th trick, if any.
+    pub fn trick_metadata(&self, i: usize) -> Option<&str> {
+        self.find_metadata(i, None)
+    }
+
+    /// Returns the payload attached to the card played by `player` in the `i`-th trick, if any.
+    pub fn card_metadata(&self, i: usize, player: pos::PlayerPos) -> Option<&str> {
+        self.find_metadata(i, Some(player))
+    }
+
+    fn find_metadata(&self, i: usize, player: Option<pos::PlayerPos>) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|&&(index, p, _)| (index, p) == (i, player))
+            .map(|(_, _, metadata)| metadata.as_str())
+    }
+
+    /// Returns the winner of the `i`-th completed trick.
+    pub fn trick_winner(&self, i: usize) -> Option<pos::PlayerPos> {
+        self.tricks().get(i).map(|trick| trick.winner)
+    }
+
+    /// Returns the points of the `i`-th completed trick, including the dix de der.
+    pub fn trick_points(&self, i: usize) -> Option<i32> {
+        let trick = self.tricks().get(i)?;
+        let mut score = trick.score_with(self.contract.trump, &*self.scoring);
+        if i == 7 {
+            score += self.dix_de_der(trick.winner.team());
+        }
+        Some(score)
+    }
+
+    // Returns the dix de der for `team`, winning the last trick.
+    fn dix_de_der(&self, team: pos::Team) -> i32 {
+        if self.rules.no_dix_de_der_on_capot && self.is_capot(team) {
+            return 0;
+        }
+        self.rules
+            .dix_de_der
+            .unwrap_or_else(|| self.scoring.last_trick_bonus())
+    }
+
+    // Returns the total points of the deal, including the dix de der set by the rules.
+    fn deal_total(&self) -> i32 {
+        let bonus = self
+            .rules
+            .dix_de_der
+            .unwrap_or_else(|| self.scoring.last_trick_bonus());
+        self.scoring.deal_total(self.contract.trump) - self.scoring.last_trick_bonus() + bonus
+    }
+
+    // Returns the completed tricks and the current one.
+    fn started_tricks(&self) -> &[trick::Trick] {
+        &self.tricks[..self.trick_count]
+    }
+
+    /// Returns the current trick.
+    pub fn current_trick(&self) -> &trick::Trick {
+        &self.tricks[self.trick_count - 1]
+    }
+
+    fn current_trick_mut(&mut self) -> &mut trick::Trick {
+        &mut self.tricks[self.trick_count - 1]
+    }
+}
+
+/// Builds a game in the middle of a deal, without replaying every card.
+///
+/// Cards in the tricks are not checked against the rules: only the structure of the deal is.
+/// This makes it easy to set up endgame tests and puzzles.
+#[derive(Clone)]
+pub struct GameStateBuilder {
+    first: pos::PlayerPos,
+    hands: [cards::Hand; 4],
+    contract: bid::Contract,
+    tricks: Vec<trick::Trick>,
+    current: Option<trick::Trick>,
+    points: Option<[i32; 2]>,
+    rules: rules::RuleSet,
+    scoring: Arc<dyn points::ScoringScheme + Send + Sync>,
+}
+
+impl GameStateBuilder {
+    /// Starts a game where `first` led the first trick, and players still hold `hands`.
+    pub fn new(first: pos::PlayerPos, hands: [cards::Hand; 4], contract: bid::Contract) -> Self {
+        GameStateBuilder {
+            first,
+            hands,
+            contract,
+            tricks: Vec::new(),
+            current: None,
+            points: None,
+            rules: rules::RuleSet::default(),
+            scoring: Arc::new(points::ClassicScoring),
+        }
+    }
+
+    /// Adds a completed trick.
+    pub fn trick(mut self, trick: trick::Trick) -> Self {
+        self.tricks.push(trick);
+        self
+    }
+
+    /// Sets the cards on the table, for a trick not complete yet.
+    ///
+    /// By default, the current trick is empty, and led by the winner of the last completed trick.
+    pub fn current_trick(mut self, trick: trick::Trick) -> Self {
+        self.current = Some(trick);
+        self
+    }
+
+    /// Sets the points won so far by each team.
+    ///
+    /// By default, points are counted from the completed tricks.
+    /// Custom points must add up to the same total.
+    pub fn points(mut self, points: [i32; 2]) -> Self {
+        self.points = Some(points);
+        self
+    }
+
+    /// Sets the rules used to play and score the game.
+    pub fn rules(mut self, rules: rules::RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Sets the scoring scheme, instead of the classic scoring.
+    pub fn scoring(mut self, scoring: Arc<dyn points::ScoringScheme + Send + Sync>) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Builds the game.
+    ///
+    /// Returns an error if a trick is not led by the winner of the previous one,
+    /// or if the hands and the tricks do not make a full deal.
+    pub fn build(self) -> Result<GameState, String> {
+        let GameStateBuilder {
+            first,
+            hands,
+            contract,
+            mut tricks,
+            current,
+            points,
+            rules,
+            scoring,
+        } = self;
+
+        if tricks.len() > 8 {
+            return Err(format!("found {} tricks instead of 8", tricks.len()));
+        }
+        let mut leader = first;
+        for (i, trick) in tricks.iter().enumerate() {
+            if trick.first != leader {
+                return Err(format!(
+                    "trick {} should be led by {}, not {}",
+                    i, leader, trick.first
+                ));
+            }
+            if !trick.is_complete() {
+                return Err(format!("trick {} is not complete", i));
+            }
+            leader = trick.winner;
+        }
+        match current {
+            Some(_) if tricks.len() == 8 => return Err("the deal is already over".to_string()),
+            Some(trick) if trick.first != leader => {
+                return Err(format!(
+                    "current trick should be led by {}, not {}",
+                    leader, trick.first
+                ))
+            }
+            Some(trick) if trick.is_complete() => {
+                return Err("current trick is complete".to_string())
+            }
+            Some(trick) => tricks.push(trick),
+            None if tricks.len() < 8 => tricks.push(trick::Trick::new(leader)),
+            None => (),
+        }
+
+        // Rebuild the dealt hands.
+        let mut dealt = hands;
+        for trick in &tricks {
+            for (p, card) in trick.iter_in_play_order() {
+                dealt[p as usize].add(card);
+            }
+        }
+        check_deal(&dealt, &contract)?;
+
+        let mut game =
+            GameState::new_with_scoring(first, dealt, contract, scoring).with_rules(rules);
+        game.players = hands;
+        game.trick_count = tricks.len();
+        game.tricks[..tricks.len()].copy_from_slice(&tricks);
+        let trick = *game.current_trick();
+        game.current = if trick.is_complete() {
+            trick.winner
+        } else {
+            trick.first.next_n(trick.len())
+        };
+
+        let mut total = [0; 2];
+        for (i, trick) in game.tricks().iter().enumerate() {
+            total[trick.winner.team() as usize] += game.trick_points(i).unwrap();
+        }
+        game.points = match points {
+            Some(points) if points[0] + points[1] != total[0] + total[1] => {
+                return Err(format!(
+                    "points {:?} do not match tricks total {}",
+                    points,
+                    total[0] + total[1]
+                ))
+            }
+            Some(points) => points,
+            None => total,
+        };
+        game.hash = game.full_hash();
+        game.check_invariants()?;
+        Ok(game)
+    }
+}
+
+// Random keys for the Zobrist hash: one per card and player, then one per current player.
+const ZOBRIST_KEYS: [u64; 32 * 4 + 4] = zobrist_keys();
+
+// Fills the keys with a splitmix64 sequence, so they are the same for every build.
+const fn zobrist_keys() -> [u64; 32 * 4 + 4] {
+    let mut keys = [0; 32 * 4 + 4];
+    let mut i = 0;
+    while i < keys.len() {
+        keys[i] = splitmix64(i as u64);
+        i += 1;
+    }
+    keys
+}
+
+const fn splitmix64(n: u64) -> u64 {
+    let mut z = n.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Order-sensitive digest of a sequence of values.
+pub(crate) fn checksum<I: IntoIterator<Item = u64>>(values: I) -> u64 {
+    values
+        .into_iter()
+        .fold(0, |digest, value| splitmix64(digest ^ value))
+}
+
+// Logs the final result of a deal, when the `tracing` feature is enabled.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn trace_result(contract: &bid::Contract, result: &GameResult) {
+    if let GameResult::GameOver {
+        winners,
+        scores,
+        fulfilled,
+        ..
+    } = *result
+    {
+        trace_event!(
+            contract = %contract,
+            winners = %winners,
+            scores = ?scores,
+            fulfilled,
+            "deal over"
+        );
+    }
+}
+
+fn card_key(player: pos::PlayerPos, card: cards::Card) -> u64 {
+    ZOBRIST_KEYS[player as usize * 32 + card.id() as usize]
+}
+
+fn player_key(player: pos::PlayerPos) -> u64 {
+    ZOBRIST_KEYS[32 * 4 + player as usize]
+}
+
+fn contract_key(contract: &bid::Contract) -> u64 {
+    let trump = cards::Trump::all()
+        .iter()
+        .position(|&t| t == contract.trump)
+        .unwrap();
+    let packed = (contract.author as u64)
+        | (trump as u64) << 2
+        | (contract.target as u64) << 5
+        | (contract.coinche_level as u64) << 10;
+    splitmix64(packed ^ 0xC0C0_C0C0_0000_0000)
+}
+
+// Checks that each hand holds 8 cards not held by anyone else,
+// and that the contract makes sense.
+fn check_deal(hands: &[cards::Hand; 4], contract: &bid::Contract) -> Result<(), String> {
+    let mut seen = cards::Hand::new();
+    for (p, hand) in hands.iter().enumerate() {
+        if hand.size() != 8 {
+            return Err(format!("P{} has {} cards instead of 8", p, hand.size()));
+        }
+        if !seen.intersection(*hand).is_empty() {
+            return Err(format!("P{} holds cards dealt to another player", p));
+        }
+        seen = seen.union(*hand);
+    }
+
+    if !(0..=2).contains(&contract.coinche_level) {
+        return Err(format!("invalid coinche level {}", contract.coinche_level));
+    }
+    for raise in &contract.raises {
+        if raise.target >= contract.target || !raise.author.is_partner(contract.author) {
+            return Err(format!(
+                "invalid raise from {} to {}",
+                raise.author, raise.target
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the move appear legal, with the default rules.
+pub fn can_play(
+    p: pos::PlayerPos,
+    card: cards::Card,
+    hand: cards::Hand,
+    trick: &trick::Trick,
+    trump: cards::Trump,
+) -> Result<(), PlayError> {
+    can_play_with(p, card, hand, trick, trump, &rules::RuleSet::default())
+}
+
+/// Returns `true` if the move appear legal, with the given rules.
+///
+/// The card is checked against the mask from `legal_cards_with`;
+/// the rules are only walked again to explain why a card is refused.
+pub fn can_play_with(
+    p: pos::PlayerPos,
+    card: cards::Card,
+    hand: cards::Hand,
+    trick: &trick::Trick,
+    trump: cards::Trump,
+    rules: &rules::RuleSet,
+) -> Result<(), PlayError> {
+    // First, we need the card to be able to play
+    if !hand.has(card) {
+        return Err(PlayError::CardMissing { card });
+    }
+
+    if legal_cards_with(p, hand, trick, trump, rules).has(card) {
+        return Ok(());
+    }
+
+    let card_suit = card.suit();
+    let starting_suit = trick.suit().unwrap();
+    if card_suit != starting_suit && hand.has_any(starting_suit) {
+        return Err(PlayError::IncorrectSuit {
+            card,
+            expected: starting_suit,
+        });
+    }
+
+    match trump.suit() {
+        // A discard while holding trumps.
+        Some(trump) if card_suit != trump && card_suit != starting_suit => {
+            Err(PlayError::InvalidPiss { card, trump })
+        }
+        // Otherwise, the card does not raise.
+        _ => check_raise(p, card, hand, trick),
+    }
+}
+
+/// Returns the cards from `hand` that player `p` may play on the given trick, with the default rules.
+///
+/// This is the set of cards for which `can_play` would succeed.
+pub fn legal_cards(
+    p: pos::PlayerPos,
+    hand: cards::Hand,
+    trick: &trick::Trick,
+    trump: cards::Trump,
+) -> cards::Hand {
+    legal_cards_with(p, hand, trick, trump, &rules::RuleSet::default())
+}
+
+/// Returns the cards from `hand` that player `p` may play on the given trick, with the given rules.
+///
+/// This is the set of cards for which `can_play_with` would succeed.
+pub fn legal_cards_with(
+    p: pos::PlayerPos,
+    hand: cards::Hand,
+    trick: &trick::Trick,
+    trump: cards::Trump,
+    rules: &rules::RuleSet,
+) -> cards::Hand {
+    let starting_suit = match trick.suit() {
+        Some(suit) if p != trick.first => suit,
+        _ => return hand,
+    };
+
+    let partner_winning = p.is_partner(trick.winner);
+    let raise = |cards, suit| {
+        if partner_winning && rules.no_overtrump_on_partner {
+            cards
+        } else {
+            raising_cards(p, cards, suit, trick)
+        }
+    };
+
+    let following = hand.suit_cards(starting_suit);
+    match trump {
+        cards::Trump::NoTrump if !following.is_empty() => following,
+        cards::Trump::NoTrump => hand,
+        cards::Trump::AllTrump if !following.is_empty() => raise(following, starting_suit),
+        cards::Trump::AllTrump => hand,
+        cards::Trump::Suit(trump) if !following.is_empty() => {
+            if starting_suit == trump {
+                raise(following, trump)
+            } else {
+                following
+            }
+        }
+        cards::Trump::Suit(trump) => {
+            let trumps = hand.suit_cards(trump);
+            if trumps.is_empty() {
+                hand
+            } else if partner_winning && !rules.must_trump_on_partner {
+                // Free to discard, but trumps may still need to raise.
+                hand.difference(trumps).union(raise(trumps, trump))
+            } else {
+                raise(trumps, trump)
+            }
+        }
+    }
+}
+
+// Returns the cards from `cards` (all in `suit`) that raise on the trick, or all of them if none can.
+fn raising_cards(
+    p: pos::PlayerPos,
+    cards: cards::Hand,
+    suit: cards::Suit,
+    trick: &trick::Trick,
+) -> cards::Hand {
+    if highest_trump(trick, suit, p) < 0 {
+        return cards;
+    }
+
+    let raising = cards.intersection(trick.beating());
+    if raising.is_empty() {
+        cards
+    } else {
+        raising
+    }
+}
+
+// Checks that `card` raises on the highest card of its suit already in the trick, if possible.
+fn check_raise(
+    p: pos::PlayerPos,
+    card: cards::Card,
+    hand: cards::Hand,
+    trick: &trick::Trick,
+) -> Result<(), PlayError> {
+    let highest = highest_trump(trick, card.suit(), p);
+    if points::trump_strength(card.rank()) < highest && has_higher(hand, card.suit(), highest) {
+        let minimum = cards::Rank::by_trump_strength()
+            .find(|&rank| points::trump_strength(rank) > highest)
+            .unwrap();
+        return Err(PlayError::NonRaisedTrump { card, minimum });
+    }
+
+    Ok(())
+}
+
+fn has_higher(hand: cards::Hand, trump: cards::Suit, strength: i32) -> bool {
+    cards::Rank::by_trump_strength()
+        .rev()
+        .take_while(|&rank| points::trump_strength(rank) > strength)
+        .any(|rank| hand.has(cards::Card::new(trump, rank)))
+}
+
+// Returns the strength of the highest card of `trump` played before `player`, or -1.
+//
+// Once a card of `trump` was played, the trick winner holds the highest one.
+fn highest_trump(trick: &trick::Trick, trump: cards::Suit, player: pos::PlayerPos) -> i32 {
+    match trick.cards[trick.winner as usize] {
+        Some(winner) if player != trick.first && winner.suit() == trump => {
+            points::trump_strength(winner.rank())
+        }
+        _ => -1,
+    }
+}
+
+// Returns the player holding both the King and Queen of trump.
+//
+// Only games with a single trump suit have a belote.
+fn find_belote(hands: &[cards::Hand; 4], trump: cards::Trump) -> Option<pos::PlayerPos> {
+    let suit = trump.suit()?;
+    let king = cards::Card::new(suit, cards::Rank::RankK);
+    let queen = cards::Card::new(suit, cards::Rank::RankQ);
+    pos::PlayerPos::all().find(|&p| {
+        let hand = hands[p as usize];
+        hand.has(king) && hand.has(queen)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_higher;
+    use super::*;
+    use crate::{bid, cards, points, pos};
+    use crate::{suit_hands, swap_cards};
+
+    #[test]
+    fn test_play_card() {
+        let mut hands = [cards::Hand::new(); 4];
+        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank8));
+        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankX));
+        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankA));
+        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank9));
+        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::Rank7));
+        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::Rank8));
+        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::Rank9));
+        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankJ));
+
+        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankQ));
+        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankK));
+        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankX));
+        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankA));
+        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::Rank7));
+        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::Rank8));
+        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::Rank9));
+        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankJ));
+
+        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank7));
+        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank8));
+        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank9));
+        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankJ));
+        hands[2].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankQ));
+        hands[2].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankK));
+        hands[2].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankQ));
+        hands[2].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankK));
+
+        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankQ));
+        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankK));
+        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankX));
+        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankA));
+        hands[3].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankX));
+        hands[3].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankA));
+        hands[3].add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7));
+        hands[3].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankJ));
+
+        let contract = bid::Contract {
+            trump: cards::Trump::Suit(cards::Suit::Heart),
+            author: pos::PlayerPos::P0,
+            target: bid::Target::Contract80,
+            coinche_level: 0,
+            raises: Vec::new(),
+        };
+
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+
+        // Wrong turn
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P1,
+                cards::Card::new(cards::Suit::Club, cards::Rank::RankX)
+            )
+            .err(),
+            Some(PlayError::TurnError {
+                expected: pos::PlayerPos::P0
+            })
+        );
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P0,
+                cards::Card::new(cards::Suit::Club, cards::Rank::Rank7)
+            )
+            .ok(),
+            Some(TrickResult::Nothing)
+        );
+        // Card missing
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P1,
+                cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7)
+            )
+            .err(),
+            Some(PlayError::CardMissing {
+                card: cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7)
+            })
+        );
+        // Wrong color
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P1,
+                cards::Card::new(cards::Suit::Spade, cards::Rank::Rank7)
+            )
+            .err(),
+            Some(PlayError::IncorrectSuit {
+                card: cards::Card::new(cards::Suit::Spade, cards::Rank::Rank7),
+                expected: cards::Suit::Club,
+            })
+        );
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P1,
+                cards::Card::new(cards::Suit::Club, cards::Rank::RankQ)
+            )
+            .ok(),
+            Some(TrickResult::Nothing)
+        );
+        // Invalid piss
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P2,
+                cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank7)
+            )
+            .err(),
+            Some(PlayError::InvalidPiss {
+                card: cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank7),
+                trump: cards::Suit::Heart,
+            })
+        );
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P2,
+                cards::Card::new(cards::Suit::Heart, cards::Rank::RankQ)
+            )
+            .ok(),
+            Some(TrickResult::Nothing)
+        );
+        // UnderTrump
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P3,
+                cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7)
+            )
+            .err(),
+            Some(PlayError::NonRaisedTrump {
+                card: cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7),
+                minimum: cards::Rank::RankK,
+            })
+        );
+        assert_eq!(
+            game.play_card(
+                pos::PlayerPos::P3,
+                cards::Card::new(cards::Suit::Heart, cards::Rank::RankJ)
+            )
+            .ok(),
+            Some(TrickResult::TrickOver(
+                pos::PlayerPos::P3,
+                game.get_game_result()
+            ))
+        );
+    }
+
+    // Plays the first legal card of the current player.
+    fn play_first_legal(game: &mut GameState) -> TrickResult {
+        let player = game.next_player();
+        for card in game.hands()[player as usize].list() {
+            if let Ok(result) = game.play_card(player, card) {
+                return result;
+            }
+        }
+        panic!("no legal card for {:?}", player);
+    }
+
+    // Plays a game where P0 holds every heart, with the given contract.
+    fn play_capot(author: pos::PlayerPos, contract: &str) -> GameResult {
+        play_capot_with(author, contract, rules::RuleSet::default())
+    }
+
+    fn play_capot_with(
+        author: pos::PlayerPos,
+        contract: &str,
+        rules: rules::RuleSet,
+    ) -> GameResult {
+        // P0 holds every trump, and wins every trick.
+        let contract = bid::Contract::parse(author, contract).unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract).with_rules(rules);
+        for _ in 0..31 {
+            play_first_legal(&mut game);
+        }
+        match play_first_legal(&mut game) {
+            TrickResult::TrickOver(_, result) => result,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_capot_scores() {
+        match play_capot(pos::PlayerPos::P0, "80♥") {
+            GameResult::GameOver {
+                winners, scores, ..
+            } => {
+                assert_eq!(winners, pos::Team::T02);
+                assert_eq!(scores, [80 + bid::CAPOT_BONUS + points::BELOTE_SCORE, 0]);
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
+
+        match play_capot(pos::PlayerPos::P0, "Capot♥") {
+            GameResult::GameOver { scores, .. } => assert_eq!(scores, [520, 0]),
+            GameResult::Nothing => panic!("game should be over"),
+        }
+
+        // An announced capot is forfeited entirely.
+        match play_capot(pos::PlayerPos::P1, "Capot♥") {
+            GameResult::GameOver { scores, .. } => {
+                assert_eq!(scores, [160 + bid::CAPOT_BONUS + points::BELOTE_SCORE, 0]);
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
+    }
+
+    #[test]
+    fn test_belote() {
+        // P0 holds every heart, including the King and Queen.
+        match play_capot(pos::PlayerPos::P0, "80♥") {
+            GameResult::GameOver { belote, .. } => assert_eq!(belote, Some(pos::Team::T02)),
+            GameResult::Nothing => panic!("game should be over"),
+        }
+
+        let mut hands = [cards::Hand::new(); 4];
+        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankK));
+        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankQ));
+        hands[2].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankK));
+        hands[3].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankQ));
+        assert_eq!(
+            find_belote(&hands, cards::Suit::Club.into()),
+            Some(pos::PlayerPos::P1)
+        );
+        assert_eq!(find_belote(&hands, cards::Suit::Heart.into()), None);
+        assert_eq!(find_belote(&hands, cards::Trump::NoTrump), None);
+    }
+
+    #[test]
+    fn test_announces() {
+        let hands = suit_hands();
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+
+        let game = GameState::new(pos::PlayerPos::P0, hands, contract.clone());
+        assert!(game.announces().is_none());
+
+        // Every player holds an 8-card sequence: the trump one wins.
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract).with_announces(true);
+        assert!(game.announces().is_none());
+        for _ in 0..4 {
+            play_first_legal(&mut game);
+        }
+        assert_eq!(game.announces().unwrap()[1].len(), 1);
+
+        for _ in 0..27 {
+            play_first_legal(&mut game);
+        }
+        match play_first_legal(&mut game) {
+            TrickResult::TrickOver(_, GameResult::GameOver { declarations, .. }) => {
+                assert_eq!(declarations, [200, 0]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    // Every combination of the trumping rules.
+    fn partner_rules() -> Vec<rules::RuleSet> {
+        let mut result = Vec::new();
+        for &must_trump_on_partner in [false, true].iter() {
+            for &no_overtrump_on_partner in [false, true].iter() {
+                result.push(rules::RuleSet {
+                    must_trump_on_partner,
+                    no_overtrump_on_partner,
+                    ..rules::RuleSet::default()
+                });
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        for rules in partner_rules() {
+            for (seed, &trump) in cards::Trump::all().iter().enumerate() {
+                let hands = crate::deal_seeded_hands([seed as u8; 32]);
+                let contract = bid::Contract::parse(pos::PlayerPos::P0, "80SA").unwrap();
+                let contract = bid::Contract { trump, ..contract };
+                let mut game =
+                    GameState::new(pos::PlayerPos::P0, hands, contract).with_rules(rules);
+
+                for _ in 0..32 {
+                    let player = game.next_player();
+                    assert_eq!(
+                        game.legal_moves(player.next()),
+                        Err(PlayError::TurnError { expected: player })
+                    );
+
+                    let legal = game.legal_moves(player).unwrap();
+                    let hand = game.hands()[player as usize];
+                    let trick = game.current_trick();
+                    for card in hand.list() {
+                        let allowed = can_play_with(player, card, hand, trick, trump, &rules);
+                        assert_eq!(
+                            legal.has(card),
+                            allowed.is_ok(),
+                            "{} with {:?}",
+                            card,
+                            trump
+                        );
+                    }
+                    play_first_legal(&mut game);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_partner_rules() {
+        use cards::{Card, Rank, Suit};
+
+        let trump = cards::Trump::Suit(Suit::Heart);
+        let mut trick = trick::Trick::new(pos::PlayerPos::P0);
+        trick.play_card(
+            pos::PlayerPos::P0,
+            Card::new(Suit::Spade, Rank::Rank7),
+            trump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P1,
+            Card::new(Suit::Heart, Rank::Rank9),
+            trump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P2,
+            Card::new(Suit::Spade, Rank::Rank8),
+            trump,
+        );
+
+        // P3's partner is winning with the 9 of trump.
+        let discard = Card::new(Suit::Club, Rank::Rank7);
+        let low = Card::new(Suit::Heart, Rank::Rank7);
+        let high = Card::new(Suit::Heart, Rank::RankJ);
+        let mut hand = cards::Hand::new();
+        hand.add(discard).add(low).add(high);
+
+        // For each combination: can discard, can under-trump.
+        let expected = [(true, false), (true, true), (false, false), (false, true)];
+        for (rules, &(can_discard, can_undertrump)) in partner_rules().iter().zip(expected.iter()) {
+            let legal = legal_cards_with(pos::PlayerPos::P3, hand, &trick, trump, rules);
+            assert!(legal.has(high));
+            assert_eq!(legal.has(discard), can_discard, "{:?}", rules);
+            assert_eq!(legal.has(low), can_undertrump, "{:?}", rules);
+        }
+    }
+
+    #[test]
+    fn test_hint() {
+        for (seed, &trump) in cards::Trump::all().iter().enumerate() {
+            let hands = crate::deal_seeded_hands([seed as u8 + 10; 32]);
+            let contract = bid::Contract::parse(pos::PlayerPos::P0, "80SA").unwrap();
+            let contract = bid::Contract { trump, ..contract };
+            let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+
+            for _ in 0..32 {
+                let player = game.next_player();
+                assert_eq!(
+                    game.hint(player.next()),
+                    Err(PlayError::TurnError { expected: player })
+                );
+                let card = game.hint(player).unwrap();
+                assert!(game.play_card(player, card).is_ok());
+            }
+            assert!(game.is_over());
+        }
+
+        // Trump is spades: P0 leads its ace, P1 trumps cheaply, the others discard low.
+        use cards::{Card, Rank, Suit};
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+        let expected = [
+            Card::new(Suit::Heart, Rank::RankA),
+            Card::new(Suit::Spade, Rank::Rank7),
+            Card::new(Suit::Club, Rank::Rank7),
+            Card::new(Suit::Diamond, Rank::Rank7),
+        ];
+        for (p, &card) in pos::PlayerPos::P0.until_n(4).zip(expected.iter()) {
+            assert_eq!(game.hint(p), Ok(card));
+            game.play_card(p, card).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_playout() {
+        use rand::SeedableRng;
+
+        let hands = crate::deal_seeded_hands([6; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P2, "90♦").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..6 {
+            play_first_legal(&mut game);
+        }
+
+        let mut rng = rand::rngs::StdRng::from_seed([1; 32]);
+        let result = game.playout_cloned(&mut rng);
+        match result {
+            GameResult::GameOver { points, .. } => assert_eq!(points[0] + points[1], 162),
+            GameResult::Nothing => panic!("game should be over"),
+        }
+        // The original game is untouched.
+        assert_eq!(game.current_trick().len(), 2);
+
+        // Same seed, same playout.
+        let mut rng = rand::rngs::StdRng::from_seed([1; 32]);
+        assert_eq!(game.playout(&mut rng), result);
+        assert!(game.is_over());
+        assert_eq!(game.playout(&mut rng), result);
+    }
+
+    #[test]
+    fn test_hash() {
+        use cards::{Card, Rank, Suit};
+
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract.clone());
+        let other = GameState::new(pos::PlayerPos::P0, suit_hands(), contract.clone());
+        assert_eq!(game.hash(), other.hash());
+
+        let start = game.hash();
+        let mut hashes = vec![start];
+        for _ in 0..9 {
+            play_first_legal(&mut game);
+            assert!(!hashes.contains(&game.hash()));
+            hashes.push(game.hash());
+        }
+
+        // Undoing brings back the previous hashes.
+        while game.undo().is_some() {
+            hashes.pop();
+            assert_eq!(Some(&game.hash()), hashes.last());
+        }
+        assert_eq!(game.hash(), start);
+
+        // The same cards played in a different order give the same hash.
+        // With hearts as trump, P0 wins every trick.
+        let card = Card::new;
+        let hearts = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut a = GameState::new(pos::PlayerPos::P0, suit_hands(), hearts);
+        let mut b = a.clone();
+        for &(ace, seven) in [(Rank::RankA, Rank::Rank7), (Rank::Rank7, Rank::RankA)].iter() {
+            for (game, rank) in [(&mut a, ace), (&mut b, seven)].iter_mut() {
+                game.play_card(pos::PlayerPos::P0, card(Suit::Heart, *rank))
+                    .unwrap();
+                for (p, suit) in [
+                    (pos::PlayerPos::P1, Suit::Spade),
+                    (pos::PlayerPos::P2, Suit::Club),
+                    (pos::PlayerPos::P3, Suit::Diamond),
+                ]
+                .iter()
+                {
+                    game.play_card(*p, card(*suit, *rank)).unwrap();
+                }
+            }
+        }
+        assert_ne!(a.tricks()[0].cards, b.tricks()[0].cards);
+        assert_eq!(a.hash(), b.hash());
+
+        // The contract is part of the hash.
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠ X").unwrap();
+        let coinched = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+        assert_ne!(coinched.hash(), start);
+    }
+
+    #[test]
+    fn test_tracker() {
+        let hands = crate::deal_seeded_hands([9; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♣").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..32 {
+            let tracker = game.tracker();
+            let held = game
+                .hands()
+                .iter()
+                .fold(cards::Hand::new(), |all, &hand| all.union(hand));
+            assert_eq!(tracker.unseen(), held);
+
+            // Inferred voids are always right.
+            for p in pos::PlayerPos::all() {
+                for suit in tracker.voids(p) {
+                    assert!(!game.hands()[p as usize].has_any(suit));
+                }
+            }
+            play_first_legal(&mut game);
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let hands = crate::deal_seeded_hands([12; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P3, "100♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract.clone());
+        let mut played = Vec::new();
+        for _ in 0..32 {
+            let player = game.next_player();
+            let card = game.legal_moves(player).unwrap().get_card();
+            game.play_card(player, card).unwrap();
+            played.push((player, card));
+        }
+
+        let replayed =
+            GameState::replay(pos::PlayerPos::P0, hands, contract.clone(), &played).unwrap();
+        assert_eq!(replayed.get_game_result(), game.get_game_result());
+        assert_eq!(replayed.hash(), game.hash());
+
+        // Partial records are fine.
+        let replayed =
+            GameState::replay(pos::PlayerPos::P0, hands, contract.clone(), &played[..10]).unwrap();
+        assert_eq!(replayed.tricks().len(), 2);
+
+        // Out of turn.
+        let mut bad = played.clone();
+        bad.swap(0, 1);
+        assert_eq!(
+            GameState::replay(pos::PlayerPos::P0, hands, contract.clone(), &bad).err(),
+            Some(PlayError::TurnError {
+                expected: pos::PlayerPos::P0
+            })
+        );
+
+        // Card from someone else's hand.
+        let mut bad = played;
+        bad[0].1 = hands[1].get_card();
+        assert_eq!(
+            GameState::replay(pos::PlayerPos::P0, hands, contract, &bad).err(),
+            Some(PlayError::CardMissing {
+                card: hands[1].get_card()
+            })
+        );
+    }
+
+    #[test]
+    fn test_annotate_moves() {
+        use cards::{Card, Rank, Suit};
+
+        // Trump is spades: P1 has no heart and must trump.
+        let mut hands = suit_hands();
+        swap_cards(
+            &mut hands,
+            (pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::Rank7)),
+            (pos::PlayerPos::P2, Card::new(Suit::Club, Rank::Rank7)),
+        );
+        swap_cards(
+            &mut hands,
+            (pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank7)),
+            (pos::PlayerPos::P3, Card::new(Suit::Diamond, Rank::Rank7)),
+        );
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+
+        assert!(game
+            .annotate_moves(pos::PlayerPos::P1)
+            .iter()
+            .all(|(_, r)| *r
+                == Err(PlayError::TurnError {
+                    expected: pos::PlayerPos::P0
+                })));
+        assert!(game
+            .annotate_moves(pos::PlayerPos::P0)
+            .iter()
+            .all(|(_, r)| r.is_ok()));
+
+        game.play_card(pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::RankA))
+            .unwrap();
+        let annotated = game.annotate_moves(pos::PlayerPos::P1);
+        assert_eq!(annotated.len(), 8);
+        for (card, result) in annotated {
+            let expected = if card.suit() == Suit::Spade {
+                Ok(())
+            } else {
+                Err(PlayError::InvalidPiss {
+                    card,
+                    trump: Suit::Spade,
+                })
+            };
+            assert_eq!(result, expected, "{}", card);
+        }
+
+        // P2 still has a heart.
+        game.play_card(pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank8))
+            .unwrap();
+        for (card, result) in game.annotate_moves(pos::PlayerPos::P2) {
+            let expected = if card.suit() == Suit::Heart {
+                Ok(())
+            } else {
+                Err(PlayError::IncorrectSuit {
+                    card,
+                    expected: Suit::Heart,
+                })
+            };
+            assert_eq!(result, expected, "{}", card);
+        }
+    }
+
+    #[test]
+    fn test_move_tags() {
+        use cards::{Card, Rank, Suit};
+
+        // P2 holds the jack of trump, and P0 the ace of clubs.
+        let mut hands = suit_hands();
+        hands[0].remove(Card::new(Suit::Heart, Rank::RankJ));
+        hands[0].add(Card::new(Suit::Club, Rank::RankA));
+        hands[2].remove(Card::new(Suit::Club, Rank::RankA));
+        hands[2].add(Card::new(Suit::Heart, Rank::RankJ));
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+
+        assert_eq!(
+            game.move_tags(pos::PlayerPos::P1),
+            Err(PlayError::TurnError {
+                expected: pos::PlayerPos::P0
+            })
+        );
+        let tags = game.move_tags(pos::PlayerPos::P0).unwrap();
+        assert_eq!(tags.len(), 8);
+        assert!(tags.iter().all(|(_, tags)| *tags == [MoveTag::Winning]));
+
+        // P1 has no trump and cannot do anything about it.
+        game.play_card(pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::Rank7))
+            .unwrap();
+        let tags = game.move_tags(pos::PlayerPos::P1).unwrap();
+        assert!(tags.contains(&(
+            Card::new(Suit::Spade, Rank::RankA),
+            vec![MoveTag::Loser, MoveTag::GivesPoints(11)]
+        )));
+        assert!(tags.contains(&(Card::new(Suit::Spade, Rank::Rank7), vec![MoveTag::Loser])));
+
+        // P2 must spend the jack on its partner's trick.
+        game.play_card(pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank7))
+            .unwrap();
+        assert_eq!(
+            game.move_tags(pos::PlayerPos::P2).unwrap(),
+            vec![(
+                Card::new(Suit::Heart, Rank::RankJ),
+                vec![MoveTag::Winning, MoveTag::BurnsMasterTrump]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_projected_result() {
+        // P0 holds every heart, and wins every trick.
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♥").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+        for _ in 0..12 {
+            play_first_legal(&mut game);
+        }
+
+        let team_points = |result| match result {
+            GameResult::GameOver {
+                points,
+                winners,
+                capot,
+                ..
+            } => {
+                assert_eq!(winners, pos::Team::T02);
+                assert_eq!(capot, None);
+                points
+            }
+            GameResult::Nothing => panic!("no projected result"),
+        };
+        let won = game.points();
+        assert_eq!(team_points(game.projected_result(Projection::Stop)), won);
+        assert_eq!(
+            team_points(game.projected_result(Projection::Proportional)),
+            [162, 0]
+        );
+
+        // Without any trick, the points are split evenly.
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♥").unwrap();
+        let game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+        match game.projected_result(Projection::Proportional) {
+            GameResult::GameOver {
+                points, fulfilled, ..
+            } => {
+                assert_eq!(points, [81, 81]);
+                assert!(fulfilled);
+            }
+            GameResult::Nothing => panic!("no projected result"),
+        }
+
+        // Once over, the projection is the actual result.
+        let mut game = game;
+        for _ in 0..32 {
+            play_first_legal(&mut game);
+        }
+        assert_eq!(
+            game.projected_result(Projection::Stop),
+            game.get_game_result()
+        );
+    }
+
+    #[test]
+    fn test_claim() {
+        // P0 holds every trump, and wins every trick.
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+        // Too early to claim, even for a sure trick.
+        assert_eq!(
+            game.clone().claim(pos::PlayerPos::P0, 1),
+            Err(PlayError::InvalidClaim)
+        );
+        for _ in 0..16 {
+            play_first_legal(&mut game);
+        }
+        assert_eq!(game.tricks_remaining(), MAX_CLAIM_TRICKS);
+        assert_eq!(
+            game.clone().claim(pos::PlayerPos::P1, 1),
+            Err(PlayError::InvalidClaim)
+        );
+        assert_eq!(
+            game.clone().claim(pos::PlayerPos::P0, 5),
+            Err(PlayError::InvalidClaim)
+        );
+        match game.claim(pos::PlayerPos::P2, 4) {
+            Ok(GameResult::GameOver { capot, .. }) => assert_eq!(capot, Some(pos::Team::T02)),
+            other => panic!("unexpected claim result: {:?}", other),
+        }
+        assert!(game.is_over());
+        assert!(!game.drain_events().is_empty());
+
+        // Near the end of a random deal, check claims against the actual outcome.
+        let hands = crate::deal_seeded_hands([14; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♦").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..18 {
+            play_first_legal(&mut game);
+        }
+        let player = game.next_player().next();
+        let before = game.tricks().len();
+        let best = (0..=game.tricks_remaining())
+            .rev()
+            .find(|&n| game.clone().claim(player, n).is_ok())
+            .unwrap();
+        game.claim(player, best).unwrap();
+        let won = game.tricks()[before..]
+            .iter()
+            .filter(|trick| trick.winner.team() == player.team())
+            .count();
+        assert!(won >= best);
+    }
+
+    #[test]
+    fn test_concede() {
+        let hands = crate::deal_seeded_hands([15; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "100♣ X").unwrap();
+        let game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        let mut game = game.with_rules(rules::RuleSet {
+            failure: rules::FailureScoring::FlatPlusContract,
+            ..rules::RuleSet::default()
+        });
+        for _ in 0..10 {
+            play_first_legal(&mut game);
+        }
+        let won = game.points();
+
+        // The taking team gives up.
+        match game.clone().concede(pos::Team::T13) {
+            Ok(GameResult::GameOver {
+                points,
+                winners,
+                scores,
+                fulfilled,
+                conceded,
+                capot,
+                belote,
+                ..
+            }) => {
+                assert_eq!(winners, pos::Team::T02);
+                assert_eq!(points, [162 - won[1], won[1]]);
+                let mut expected = [(160 + 100) * 2, 0];
+                if let Some(team) = belote {
+                    expected[team as usize] += points::BELOTE_SCORE;
+                }
+                assert_eq!(scores, expected);
+                assert!(!fulfilled);
+                assert_eq!(conceded, Some(pos::Team::T13));
+                assert_eq!(capot, None);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The defense gives up.
+        let result = game.concede(pos::Team::T02).unwrap();
+        match result {
+            GameResult::GameOver {
+                winners, fulfilled, ..
+            } => {
+                assert_eq!(winners, pos::Team::T13);
+                assert!(fulfilled);
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
+        assert!(game.is_over());
+        assert_eq!(game.get_game_result(), result);
+        assert_eq!(game.concede(pos::Team::T13), Err(PlayError::GameOver));
+        let player = game.next_player();
+        let card = game.hands()[player as usize].get_card();
+        assert_eq!(game.play_card(player, card), Err(PlayError::GameOver));
+        assert_eq!(game.legal_moves(player), Err(PlayError::GameOver));
+        assert_eq!(game.claim(player, 1), Err(PlayError::GameOver));
+        match game.drain_events().last() {
+            Some(GameEvent::GameEnded(ended)) => assert_eq!(*ended, result),
+            other => panic!("unexpected last event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_renounce() {
+        let hands = crate::deal_seeded_hands([5; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♦").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        play_first_legal(&mut game);
+        let (card, _) = game
+            .annotate_moves(pos::PlayerPos::P1)
+            .into_iter()
+            .find(|(_, result)| result.is_err())
+            .unwrap();
+
+        // By default, the card is rejected.
+        assert!(game.clone().play_card(pos::PlayerPos::P1, card).is_err());
+
+        let mut game = game.with_rules(rules::RuleSet {
+            record_renounces: true,
+            ..rules::RuleSet::default()
+        });
+        game.play_card(pos::PlayerPos::P1, card).unwrap();
+        assert_eq!(game.renounce(), Some((pos::PlayerPos::P1, card)));
+        game.undo();
+        assert_eq!(game.renounce(), None);
+        game.play_card(pos::PlayerPos::P1, card).unwrap();
+
+        while !game.is_over() {
+            play_first_legal(&mut game);
+        }
+        match game.get_game_result() {
+            GameResult::GameOver {
+                winners,
+                scores,
+                fulfilled,
+                renounce,
+                ..
+            } => {
+                assert_eq!(winners, pos::Team::T02);
+                assert!(fulfilled);
+                assert_eq!(renounce, Some(pos::Team::T13));
+                assert_eq!(scores[1], 0);
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
+    }
+
+    #[test]
+    fn test_play_forced() {
+        use cards::{Card, Rank, Suit};
+
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+
+        // Everyone holds a full suit: nobody is forced yet.
+        assert!(game.play_forced().is_empty());
+        game.play_card(pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::RankA))
+            .unwrap();
+        assert!(game.play_forced().is_empty());
+
+        // On the last trick, every card is forced.
+        for _ in 1..28 {
+            play_first_legal(&mut game);
+        }
+        let played = game.play_forced();
+        assert_eq!(played.len(), 4);
+        assert_eq!(played[0].0, pos::PlayerPos::P0);
+        assert!(game.is_over());
+        assert!(game.play_forced().is_empty());
+    }
+
+    #[test]
+    fn test_view_for() {
+        let hands = crate::deal_seeded_hands([3; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♣").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..5 {
+            play_first_legal(&mut game);
+        }
+
+        let view = game.view_for(pos::PlayerPos::P2);
+        assert_eq!(view.hand, game.hands()[2]);
+        assert_eq!(view.next_player, game.next_player());
+        let next = game.view_for(game.next_player());
+        assert_eq!(next.legal_moves, game.legal_moves(next.pos).unwrap());
+        if game.next_player() != pos::PlayerPos::P2 {
+            assert_eq!(view.legal_moves, cards::Hand::new());
+        }
+        assert!(view.last_trick.is_some());
+        assert_eq!(view.current_trick.cards, game.current_trick().cards);
+
+        // Other hands never leak into the serialized view.
+        let json = serde_json::to_string(&view).unwrap();
+        let restored: PlayerView = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.hand, view.hand);
+        for &p in [pos::PlayerPos::P0, pos::PlayerPos::P1, pos::PlayerPos::P3].iter() {
+            let hand = serde_json::to_string(&game.hands()[p as usize]).unwrap();
+            assert!(!json.contains(&hand));
+        }
+    }
+
+    #[test]
+    fn test_spectator_view() {
+        let hands = crate::deal_seeded_hands([3; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♣").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..6 {
+            play_first_legal(&mut game);
+        }
+
+        let view = game.spectator_view(RevealPolicy::Hidden);
+        assert!(view.hands.is_none());
+        assert_eq!(view.tricks.len(), 1);
+        assert_eq!(view.current_trick.unwrap().len(), 2);
+        assert_eq!(view.next_player, game.next_player());
+        assert_eq!(view.points, game.points());
+
+        // Spectators stay one trick behind, and do not see the hands.
+        let view = game.spectator_view(RevealPolicy::Delayed);
+        let trick = *game.current_trick();
+        let last = game.tricks()[0];
+        assert!(view.hands.is_none());
+        assert!(view.tricks.is_empty());
+        assert_eq!(view.current_trick, Some(last));
+        assert_eq!(view.next_player, last.winner);
+        assert_eq!(view.points, [0, 0]);
+        let json = serde_json::to_string(&view).unwrap();
+        assert!(!json.contains(&serde_json::to_string(&trick).unwrap()));
+
+        // Before the first trick is complete, nothing is shown.
+        let fresh = GameState::new(pos::PlayerPos::P0, hands, game.contract().clone());
+        let view = fresh.spectator_view(RevealPolicy::Delayed);
+        assert!(view.hands.is_none());
+        assert!(view.current_trick.is_none());
+        assert_eq!(view.next_player, pos::PlayerPos::P0);
+
+        // Once the game is over, everything is shown.
+        while !game.is_over() {
+            play_first_legal(&mut game);
+        }
+        let view = game.spectator_view(RevealPolicy::Delayed);
+        assert_eq!(view.tricks.len(), 8);
+        assert!(view.hands.unwrap().iter().all(|hand| hand.is_empty()));
+    }
+
+    #[test]
+    fn test_try_new() {
+        let hands = crate::deal_seeded_hands([4; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♣").unwrap();
+        assert!(GameState::try_new(pos::PlayerPos::P0, hands, contract.clone()).is_ok());
+
+        // Shared card.
+        let mut bad = hands;
+        bad[1].remove(bad[1].get_card());
+        bad[1].add(bad[0].get_card());
+        assert!(GameState::try_new(pos::PlayerPos::P0, bad, contract.clone()).is_err());
+
+        // Missing card.
+        let mut bad = hands;
+        bad[3].remove(bad[3].get_card());
+        assert!(GameState::try_new(pos::PlayerPos::P0, bad, contract.clone()).is_err());
+
+        // Nonsense contracts.
+        let bad = bid::Contract {
+            coinche_level: 3,
+            ..contract.clone()
+        };
+        assert!(GameState::try_new(pos::PlayerPos::P0, hands, bad).is_err());
+
+        let bad = bid::Contract {
+            raises: vec![bid::Raise {
+                author: pos::PlayerPos::P1,
+                target: bid::Target::Contract80,
+            }],
+            ..contract
+        };
+        assert!(GameState::try_new(pos::PlayerPos::P0, hands, bad).is_err());
+    }
+
+    #[test]
+    fn test_undo() {
+        let hands = crate::deal_seeded_hands([5; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♦").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        assert_eq!(game.undo(), None);
+
+        let mut snapshots = Vec::new();
+        for _ in 0..32 {
+            snapshots.push((game.hands(), game.next_player(), game.points));
+            play_first_legal(&mut game);
+        }
+        assert!(game.is_over());
+
+        // Take everything back, one card at a time.
+        while let Some((player, card)) = game.undo() {
+            let (hands, next, points) = snapshots.pop().unwrap();
+            assert_eq!(player, next);
+            assert!(hands[player as usize].has(card));
+            assert_eq!(game.hands(), hands);
+            assert_eq!(game.next_player(), next);
+            assert_eq!(game.points, points);
+        }
+        assert!(snapshots.is_empty());
+        assert_eq!(game.hands(), hands);
+        assert_eq!(game.trick_count, 1);
+        assert!(game.drain_events().is_empty());
+
+        // Undoing a card after a concession cancels both.
+        for _ in 0..5 {
+            play_first_legal(&mut game);
+        }
+        game.drain_events();
+        let player = game.next_player();
+        play_first_legal(&mut game);
+        game.concede(pos::Team::T13).unwrap();
+        assert!(game.is_over());
+
+        let (undone, card) = game.undo().unwrap();
+        assert_eq!(undone, player);
+        assert!(!game.is_over());
+        assert_eq!(game.get_game_result(), GameResult::Nothing);
+        assert!(game.drain_events().is_empty());
+        game.play_card(player, card).unwrap();
+        assert_eq!(
+            game.drain_events(),
+            vec![GameEvent::CardPlayed {
+                pos: player,
+                card,
+                metadata: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tricks_history() {
+        let hands = crate::deal_seeded_hands([4; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P3, "90♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        assert!(game.tricks().is_empty());
+        assert_eq!(game.remaining_players().len(), 4);
+        assert_eq!(game.trick_number(), 1);
+        assert_eq!(game.tricks_remaining(), 8);
+        assert_eq!(game.trick_winner(0), None);
+        assert_eq!(game.last_trick_info(), Err(PlayError::NoLastTrick));
+
+        for _ in 0..6 {
+            play_first_legal(&mut game);
+        }
+        assert_eq!(game.tricks().len(), 1);
+        let info = game.last_trick_info().unwrap();
+        assert_eq!(info.index, 0);
+        assert_eq!(info.trick, game.tricks()[0]);
+        assert_eq!(info.winner, game.tricks()[0].winner);
+        assert_eq!(Some(info.points), game.trick_points(0));
+        assert_eq!(game.trick_winner(0), Some(game.tricks()[0].winner));
+        let next = game.next_player();
+        assert_eq!(game.remaining_players(), vec![next, next.next()]);
+        assert_eq!(game.trick_number(), 2);
+        assert_eq!(game.tricks_remaining(), 7);
+        assert!(!game.is_over());
+
+        for _ in 6..32 {
+            play_first_legal(&mut game);
+        }
+        assert_eq!(game.tricks().len(), 8);
+        assert!(game.remaining_players().is_empty());
+        assert_eq!(game.trick_number(), 8);
+        assert_eq!(game.tricks_remaining(), 0);
+        assert!(game.is_over());
+
+        let mut points = [0; 2];
+        for i in 0..8 {
+            let team = game.trick_winner(i).unwrap().team();
+            points[team as usize] += game.trick_points(i).unwrap();
+        }
+        assert_eq!(points, game.points());
+        assert_eq!(game.trick_points(8), None);
+
+        // The dix de der is included.
+        let info = game.last_trick_info().unwrap();
+        assert_eq!(info.index, 7);
+        assert_eq!(info.points, info.trick.score(game.contract().trump) + 10);
+    }
+
+    #[test]
+    fn test_running_points() {
+        let hands = crate::deal_seeded_hands([6; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80TA").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        let total =
+            points::ScoringScheme::deal_total(&points::ClassicScoring, cards::Trump::AllTrump);
+        assert_eq!(game.remaining_points(), total);
+
+        for _ in 0..4 {
+            play_first_legal(&mut game);
+        }
+        let won = game.team_points(pos::Team::T02) + game.team_points(pos::Team::T13);
+        assert_eq!(won, game.trick_points(0).unwrap());
+        assert_eq!(game.remaining_points(), total - won);
+
+        for _ in 4..32 {
+            play_first_legal(&mut game);
+        }
+        assert_eq!(game.remaining_points(), 0);
+    }
+
+    // Counts the allocations made by each thread, to check that cloning a game does not allocate.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_clone_without_allocation() {
+        let hands = crate::deal_seeded_hands([9; 32]);
+        let contract = bid::Contract {
+            raises: vec![bid::Raise {
+                author: pos::PlayerPos::P2,
+                target: bid::Target::Contract80,
+            }],
+            ..bid::Contract::parse(pos::PlayerPos::P0, "100♠").unwrap()
+        };
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract)
+            .with_announces(true)
+            .with_events(false);
+        game.set_trick_metadata("first trick".to_string());
+        for _ in 0..5 {
+            play_first_legal(&mut game);
+        }
+
+        let before = ALLOCATIONS.with(|n| n.get());
+        let copy = game.clone();
+        assert_eq!(ALLOCATIONS.with(|n| n.get()), before);
+        assert_eq!(copy.checksum(), game.checksum());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let hands = crate::deal_seeded_hands([8; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "100♥ X").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P1, hands, contract);
+        for _ in 0..13 {
+            play_first_legal(&mut game);
+        }
+
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+        let snapshot: GameSnapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = GameState::restore(snapshot.clone()).unwrap();
+        assert_eq!(restored.hands(), game.hands());
+        assert_eq!(restored.points(), game.points());
+        assert_eq!(restored.next_player(), game.next_player());
+        assert_eq!(restored.contract(), game.contract());
+
+        // Both games go on the same way.
+        for _ in 13..32 {
+            assert_eq!(play_first_legal(&mut restored), play_first_legal(&mut game));
+        }
+
+        // Tampered snapshots are rejected.
+        let mut bad = snapshot.clone();
+        bad.points[0] += 10;
+        assert!(GameState::restore(bad).is_err());
+
+        let mut bad = snapshot.clone();
+        bad.current = bad.current.next();
+        assert!(GameState::restore(bad).is_err());
+
+        let mut bad = snapshot;
+        let card = bad.hands[0].get_card();
+        bad.hands[1].add(card);
+        assert!(GameState::restore(bad).is_err());
+    }
+
+    #[test]
+    fn test_metadata() {
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+
+        game.set_trick_metadata("first".to_string());
+        for i in 0..4 {
+            let player = game.next_player();
+            let card = game.legal_moves(player).unwrap().get_card();
+            game.play_card_with_metadata(player, card, format!("card {}", i))
+                .unwrap();
+        }
+        play_first_legal(&mut game);
+
+        assert_eq!(game.trick_metadata(0), Some("first"));
+        assert_eq!(game.trick_metadata(1), None);
+        assert_eq!(game.card_metadata(0, pos::PlayerPos::P2), Some("card 2"));
+        assert_eq!(game.card_metadata(1, pos::PlayerPos::P0), None);
+
+        let events = game.drain_events();
+        assert_eq!(
+            events[1],
+            GameEvent::CardPlayed {
+                pos: pos::PlayerPos::P1,
+                card: game.tricks()[0].cards[1].unwrap(),
+                metadata: Some("card 1".to_string()),
+            }
+        );
+        assert!(events.iter().any(|e| matches!(
+            e,
+            GameEvent::TrickWon {
+                metadata: Some(m),
+                ..
+            } if m == "first"
+        )));
+
+        // Taking a card back drops its payload, but not the trick's.
+        game.undo();
+        game.undo();
+        assert_eq!(game.card_metadata(0, pos::PlayerPos::P3), None);
+        assert_eq!(game.trick_metadata(0), Some("first"));
+    }
+
+    #[test]
+    fn test_diff() {
+        let hands = crate::deal_seeded_hands([9; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P3, "90♣").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..5 {
+            play_first_legal(&mut game);
+        }
+        let mut older = game.clone();
+        for _ in 0..6 {
+            play_first_legal(&mut game);
         }
 
-        let taking_team = self.contract.author.team();
-        let taking_points = self.points[taking_team as usize];
+        let patch = game.diff(&older);
+        assert_eq!(patch.undo, 0);
+        assert_eq!(patch.plays.len(), 6);
+        let json = serde_json::to_string(&patch).unwrap();
+        let patch: StatePatch = serde_json::from_str(&json).unwrap();
+        older.apply_patch(&patch).unwrap();
+        assert_eq!(older.hash(), game.hash());
+        assert_eq!(older.hands(), game.hands());
+        assert_eq!(older.points(), game.points());
+        assert!(game.diff(&older).plays.is_empty());
+
+        // Cards taken back are undone first.
+        let newer = {
+            let mut newer = game.clone();
+            newer.undo();
+            newer.undo();
+            newer.concede(pos::Team::T02).unwrap();
+            newer
+        };
+        let patch = newer.diff(&game);
+        assert_eq!(patch.undo, 2);
+        assert!(patch.plays.is_empty());
+        game.apply_patch(&patch).unwrap();
+        assert_eq!(game.hash(), newer.hash());
+        assert!(game.is_over());
+
+        // A patch for another state is refused, and changes nothing.
+        let before = older.hash();
+        let mut bad = patch;
+        bad.undo = 0;
+        assert!(older.apply_patch(&bad).is_err());
+        assert_eq!(older.hash(), before);
+    }
 
-        let capot = self.is_capot(taking_team);
+    #[test]
+    fn test_checksum() {
+        let hands = crate::deal_seeded_hands([11; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        let mut other = game.clone();
+        assert_eq!(game.checksum(), other.checksum());
+
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..12 {
+            assert!(seen.insert(game.checksum()));
+            play_first_legal(&mut game);
+            play_first_legal(&mut other);
+            assert_eq!(game.checksum(), other.checksum());
+        }
 
-        let victory = self.contract.target.victory(taking_points, capot);
+        other.concede(pos::Team::T13).unwrap();
+        assert!(game.checksum() != other.checksum());
+    }
 
-        let winners = if victory {
-            taking_team
-        } else {
-            taking_team.opponent()
-        };
+    #[test]
+    fn test_events() {
+        // P0 holds every heart, including the belote.
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, suit_hands(), contract);
+
+        let mut events = Vec::new();
+        for _ in 0..32 {
+            play_first_legal(&mut game);
+            events.extend(game.drain_events());
+        }
+        assert!(game.drain_events().is_empty());
 
-        // TODO: Allow for variants in scoring. (See wikipedia article)
-        let mut scores = [0; 2];
-        if victory {
-            scores[winners as usize] = self.contract.target.score();
-        } else {
-            scores[winners as usize] = 160;
+        let count = |f: &dyn Fn(&GameEvent) -> bool| events.iter().filter(|e| f(e)).count();
+        assert_eq!(count(&|e| matches!(e, GameEvent::CardPlayed { .. })), 32);
+        assert_eq!(count(&|e| matches!(e, GameEvent::TrickWon { .. })), 8);
+        assert_eq!(
+            count(&|e| matches!(
+                e,
+                GameEvent::BeloteAnnounced {
+                    rebelote: false,
+                    ..
+                }
+            )),
+            1
+        );
+        assert_eq!(
+            count(&|e| matches!(e, GameEvent::BeloteAnnounced { rebelote: true, .. })),
+            1
+        );
+        match events.last() {
+            Some(GameEvent::GameEnded(GameResult::GameOver { winners, .. })) => {
+                assert_eq!(*winners, pos::Team::T02)
+            }
+            other => panic!("unexpected last event: {:?}", other),
         }
 
-        GameResult::GameOver {
-            points: self.points,
-            winners,
-            scores,
+        // Events can be sent to clients.
+        let json = serde_json::to_string(&events).unwrap();
+        let received: Vec<GameEvent> = serde_json::from_str(&json).unwrap();
+        assert_eq!(received, events);
+
+        // Events can be disabled.
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut game =
+            GameState::new(pos::PlayerPos::P0, suit_hands(), contract).with_events(false);
+        for _ in 0..32 {
+            play_first_legal(&mut game);
         }
+        assert!(game.drain_events().is_empty());
     }
 
-    fn is_capot(&self, team: pos::Team) -> bool {
-        for trick in &self.tricks {
-            if trick.winner.team() != team {
-                return false;
+    #[test]
+    fn test_result_breakdown() {
+        match play_capot(pos::PlayerPos::P1, "100♥ X") {
+            GameResult::GameOver {
+                contract,
+                fulfilled,
+                margin,
+                last_trick,
+                capot,
+                multiplier,
+                ..
+            } => {
+                assert_eq!(contract.target, bid::Target::Contract100);
+                assert!(!fulfilled);
+                assert_eq!(margin, Some(-100));
+                assert_eq!(last_trick, pos::Team::T02);
+                assert_eq!(capot, Some(pos::Team::T02));
+                assert_eq!(multiplier, 2);
             }
+            GameResult::Nothing => panic!("game should be over"),
         }
 
-        true
-    }
+        match play_capot(pos::PlayerPos::P0, "80♥") {
+            GameResult::GameOver {
+                fulfilled, margin, ..
+            } => {
+                assert!(fulfilled);
+                assert_eq!(margin, Some(points::DEAL_TOTAL + points::BELOTE_SCORE - 80));
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
 
-    /// Returns the cards of all players
-    pub fn hands(&self) -> [cards::Hand; 4] {
-        self.players
+        // A capot has no margin in points.
+        match play_capot(pos::PlayerPos::P0, "Capot♥") {
+            GameResult::GameOver {
+                fulfilled, margin, ..
+            } => {
+                assert!(fulfilled);
+                assert_eq!(margin, None);
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
     }
 
-    fn is_over(&self) -> bool {
-        self.tricks.len() == 8
-    }
+    #[test]
+    fn test_dix_de_der_rules() {
+        let dix_de_der = |result| match result {
+            GameResult::GameOver {
+                points, dix_de_der, ..
+            } => (points[0], dix_de_der),
+            GameResult::Nothing => panic!("game should be over"),
+        };
 
-    /// Return the last trick, if possible
-    pub fn last_trick(&self) -> Result<&trick::Trick, PlayError> {
-        if self.tricks.len() == 1 {
-            Err(PlayError::NoLastTrick)
-        } else {
-            let i = self.tricks.len() - 2;
-            Ok(&self.tricks[i])
-        }
-    }
+        let rules = rules::RuleSet::default();
+        assert_eq!(
+            dix_de_der(play_capot_with(pos::PlayerPos::P0, "80♥", rules)),
+            (162, Some(pos::Team::T02))
+        );
 
-    /// Returns the current trick.
-    pub fn current_trick(&self) -> &trick::Trick {
-        let i = self.tricks.len() - 1;
-        &self.tricks[i]
-    }
+        let rules = rules::RuleSet {
+            dix_de_der: Some(20),
+            ..rules::RuleSet::default()
+        };
+        assert_eq!(
+            dix_de_der(play_capot_with(pos::PlayerPos::P0, "80♥", rules)),
+            (172, Some(pos::Team::T02))
+        );
 
-    fn current_trick_mut(&mut self) -> &mut trick::Trick {
-        let i = self.tricks.len() - 1;
-        &mut self.tricks[i]
-    }
-}
+        let rules = rules::RuleSet {
+            no_dix_de_der_on_capot: true,
+            ..rules::RuleSet::default()
+        };
+        assert_eq!(
+            dix_de_der(play_capot_with(pos::PlayerPos::P0, "80♥", rules)),
+            (152, None)
+        );
 
-/// Returns `true` if the move appear legal.
-pub fn can_play(
-    p: pos::PlayerPos,
-    card: cards::Card,
-    hand: cards::Hand,
-    trick: &trick::Trick,
-    trump: cards::Suit,
-) -> Result<(), PlayError> {
-    // First, we need the card to be able to play
-    if !hand.has(card) {
-        return Err(PlayError::CardMissing);
+        // Without a capot, the dix de der is still awarded.
+        let hands = crate::deal_seeded_hands([16; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract).with_rules(rules);
+        for _ in 0..32 {
+            play_first_legal(&mut game);
+        }
+        let last = game.tricks()[7].winner.team();
+        match game.get_game_result() {
+            GameResult::GameOver {
+                points,
+                dix_de_der,
+                capot,
+                ..
+            } => {
+                assert_eq!(capot, None);
+                assert_eq!(points[0] + points[1], 162);
+                assert_eq!(dix_de_der, Some(last));
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
     }
 
-    if p == trick.first {
-        return Ok(());
-    }
+    #[test]
+    fn test_rule_set() {
+        let hands = crate::deal_seeded_hands([7; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♠").unwrap();
+        let rules = rules::RuleSet {
+            success: rules::SuccessScoring::ContractPlusPoints,
+            ..rules::RuleSet::default()
+        };
 
-    let card_suit = card.suit();
-    let starting_suit = trick.suit().unwrap();
-    if card_suit != starting_suit {
-        if hand.has_any(starting_suit) {
-            return Err(PlayError::IncorrectSuit);
+        let mut plain = GameState::new(pos::PlayerPos::P0, hands, contract.clone());
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract).with_rules(rules);
+        assert_eq!(game.rules(), rules);
+        for _ in 0..31 {
+            play_first_legal(&mut plain);
+            play_first_legal(&mut game);
         }
 
-        if card_suit != trump {
-            let partner_winning = p.is_partner(trick.winner);
-            if !partner_winning && hand.has_any(trump) {
-                return Err(PlayError::InvalidPiss);
+        match (play_first_legal(&mut plain), play_first_legal(&mut game)) {
+            (
+                TrickResult::TrickOver(_, GameResult::GameOver { scores: base, .. }),
+                TrickResult::TrickOver(_, GameResult::GameOver { points, scores, .. }),
+            ) => {
+                // Contract fulfilled: both teams keep their points.
+                assert_eq!(base, [0, 80]);
+                assert_eq!(scores, [points[0], 80 + points[1]]);
             }
+            other => panic!("unexpected results: {:?}", other),
         }
     }
 
-    // One must raise when playing trump
-    if card_suit == trump {
-        let highest = highest_trump(trick, trump, p);
-        if points::trump_strength(card.rank()) < highest && has_higher(hand, card_suit, highest) {
-            return Err(PlayError::NonRaisedTrump);
+    #[test]
+    fn test_failure_scoring() {
+        let rules = rules::RuleSet {
+            failure: rules::FailureScoring::FlatPlusContract,
+            ..rules::RuleSet::default()
+        };
+
+        match play_capot_with(pos::PlayerPos::P1, "100♥", rules) {
+            GameResult::GameOver { scores, .. } => {
+                assert_eq!(
+                    scores,
+                    [160 + 100 + bid::CAPOT_BONUS + points::BELOTE_SCORE, 0]
+                );
+            }
+            GameResult::Nothing => panic!("game should be over"),
+        }
+
+        match play_capot_with(pos::PlayerPos::P1, "100♥ X", rules) {
+            GameResult::GameOver { scores, .. } => {
+                assert_eq!(
+                    scores,
+                    [2 * (160 + 100) + bid::CAPOT_BONUS + points::BELOTE_SCORE, 0]
+                );
+            }
+            GameResult::Nothing => panic!("game should be over"),
         }
     }
 
-    Ok(())
-}
+    #[test]
+    fn test_coinche_scores() {
+        // Coinched win: the contract is doubled.
+        match play_capot(pos::PlayerPos::P0, "Capot♥ X") {
+            GameResult::GameOver { scores, .. } => assert_eq!(scores, [1020, 0]),
+            GameResult::Nothing => panic!("game should be over"),
+        }
 
-fn has_higher(hand: cards::Hand, trump: cards::Suit, strength: i32) -> bool {
-    for ri in 0..8 {
-        let rank = cards::Rank::from_n(ri);
-        if points::trump_strength(rank) > strength && hand.has(cards::Card::new(trump, rank)) {
-            return true;
+        // Surcoinched loss: the defense gets 4 times the usual score, plus its capot and belote.
+        match play_capot(pos::PlayerPos::P1, "80♥ XX") {
+            GameResult::GameOver {
+                winners, scores, ..
+            } => {
+                assert_eq!(winners, pos::Team::T02);
+                assert_eq!(
+                    scores,
+                    [4 * 160 + bid::CAPOT_BONUS + points::BELOTE_SCORE, 0]
+                );
+            }
+            GameResult::Nothing => panic!("game should be over"),
         }
     }
 
-    false
-}
+    #[test]
+    fn test_full_game() {
+        let hands = crate::deal_seeded_hands([7; 32]);
+        let contract = bid::Contract {
+            trump: cards::Trump::Suit(cards::Suit::Spade),
+            author: pos::PlayerPos::P1,
+            target: bid::Target::Contract80,
+            coinche_level: 0,
+            raises: Vec::new(),
+        };
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
 
-fn highest_trump(trick: &trick::Trick, trump: cards::Suit, player: pos::PlayerPos) -> i32 {
-    let mut highest = -1;
+        // A card cannot be played twice.
+        let card = hands[0].get_card();
+        game.play_card(pos::PlayerPos::P0, card).unwrap();
+        assert!(!game.hands()[0].has(card));
 
-    for p in trick.first.until(player) {
-        if trick.cards[p as usize].unwrap().suit() == trump {
-            let str = points::trump_strength(trick.cards[p as usize].unwrap().rank());
-            if str > highest {
-                highest = str;
+        for _ in 0..30 {
+            assert_eq!(game.get_game_result(), GameResult::Nothing);
+            play_first_legal(&mut game);
+        }
+
+        match play_first_legal(&mut game) {
+            TrickResult::TrickOver(_, GameResult::GameOver { points, .. }) => {
+                assert_eq!(points[0] + points[1], points::DEAL_TOTAL);
             }
+            other => panic!("unexpected result: {:?}", other),
         }
+        assert!(game.check_invariants().is_ok());
     }
 
-    highest
-}
-
-#[cfg(test)]
-mod tests {
-    use super::has_higher;
-    use super::*;
-    use crate::{bid, cards, points, pos};
-
     #[test]
-    fn test_play_card() {
-        let mut hands = [cards::Hand::new(); 4];
-        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank8));
-        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankX));
-        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankA));
-        hands[0].add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank9));
-        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::Rank7));
-        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::Rank8));
-        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::Rank9));
-        hands[0].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankJ));
-
-        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankQ));
-        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankK));
-        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankX));
-        hands[1].add(cards::Card::new(cards::Suit::Club, cards::Rank::RankA));
-        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::Rank7));
-        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::Rank8));
-        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::Rank9));
-        hands[1].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankJ));
+    fn test_can_play_trump_modes() {
+        use cards::{Card, Rank, Suit, Trump};
 
-        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank7));
-        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank8));
-        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank9));
-        hands[2].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankJ));
-        hands[2].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankQ));
-        hands[2].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankK));
-        hands[2].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankQ));
-        hands[2].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankK));
+        let mut hand = cards::Hand::new();
+        hand.add(Card::new(Suit::Spade, Rank::Rank7));
+        hand.add(Card::new(Suit::Spade, Rank::RankJ));
+        hand.add(Card::new(Suit::Heart, Rank::Rank8));
+
+        let mut trick = trick::Trick::new(pos::PlayerPos::P0);
+        let lead = Card::new(Suit::Spade, Rank::Rank9);
+        trick.play_card(pos::PlayerPos::P0, lead, Trump::AllTrump);
+
+        let p = pos::PlayerPos::P1;
+        let seven = Card::new(Suit::Spade, Rank::Rank7);
+        let jack = Card::new(Suit::Spade, Rank::RankJ);
+        let heart = Card::new(Suit::Heart, Rank::Rank8);
+
+        // Following suit is always mandatory.
+        for trump in [Trump::NoTrump, Trump::AllTrump, Trump::Suit(Suit::Club)].iter() {
+            assert_eq!(
+                can_play(p, heart, hand, &trick, *trump),
+                Err(PlayError::IncorrectSuit {
+                    card: heart,
+                    expected: Suit::Spade,
+                })
+            );
+        }
 
-        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankQ));
-        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankK));
-        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankX));
-        hands[3].add(cards::Card::new(cards::Suit::Diamond, cards::Rank::RankA));
-        hands[3].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankX));
-        hands[3].add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankA));
-        hands[3].add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7));
-        hands[3].add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankJ));
+        // Without trump, any card of the suit will do.
+        assert_eq!(can_play(p, seven, hand, &trick, Trump::NoTrump), Ok(()));
+        // With all trumps, one must raise when possible.
+        assert_eq!(
+            can_play(p, seven, hand, &trick, Trump::AllTrump),
+            Err(PlayError::NonRaisedTrump {
+                card: seven,
+                minimum: Rank::RankJ,
+            })
+        );
+        assert_eq!(
+            PlayError::NonRaisedTrump {
+                card: seven,
+                minimum: Rank::RankJ,
+            }
+            .to_string(),
+            "too weak trump played: 7♠ instead of at least J"
+        );
+        assert_eq!(can_play(p, jack, hand, &trick, Trump::AllTrump), Ok(()));
+    }
 
-        let contract = bid::Contract {
-            trump: cards::Suit::Heart,
-            author: pos::PlayerPos::P0,
-            target: bid::Target::Contract80,
-            coinche_level: 0,
+    #[test]
+    fn test_no_trump_play() {
+        use cards::{Card, Rank, Suit, Trump};
+
+        let rules = rules::RuleSet::default();
+        let hand = |cards: &[(Suit, Rank)]| {
+            let mut hand = cards::Hand::new();
+            for &(suit, rank) in cards {
+                hand.add(Card::new(suit, rank));
+            }
+            hand
         };
 
-        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract);
+        let mut trick = trick::Trick::new(pos::PlayerPos::P0);
+        trick.play_card(
+            pos::PlayerPos::P0,
+            Card::new(Suit::Heart, Rank::RankK),
+            Trump::NoTrump,
+        );
 
-        // Wrong turn
+        // Any card of the suit will do, without raising.
+        let following = hand(&[
+            (Suit::Heart, Rank::Rank7),
+            (Suit::Heart, Rank::RankA),
+            (Suit::Spade, Rank::Rank9),
+        ]);
         assert_eq!(
-            game.play_card(
+            legal_cards_with(
                 pos::PlayerPos::P1,
-                cards::Card::new(cards::Suit::Club, cards::Rank::RankX)
-            )
-            .err(),
-            Some(PlayError::TurnError)
+                following,
+                &trick,
+                Trump::NoTrump,
+                &rules
+            ),
+            hand(&[(Suit::Heart, Rank::Rank7), (Suit::Heart, Rank::RankA)])
         );
+
+        // Without the suit, any card will do.
+        let void = hand(&[(Suit::Spade, Rank::RankJ), (Suit::Club, Rank::Rank8)]);
         assert_eq!(
-            game.play_card(
-                pos::PlayerPos::P0,
-                cards::Card::new(cards::Suit::Club, cards::Rank::Rank7)
-            )
-            .ok(),
-            Some(TrickResult::Nothing)
+            legal_cards_with(pos::PlayerPos::P1, void, &trick, Trump::NoTrump, &rules),
+            void
         );
-        // Card missing
+
+        // The ten beats the king, and no other suit can win.
+        trick.play_card(
+            pos::PlayerPos::P1,
+            Card::new(Suit::Heart, Rank::RankX),
+            Trump::NoTrump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P2,
+            Card::new(Suit::Spade, Rank::RankJ),
+            Trump::NoTrump,
+        );
+        assert_eq!(trick.winner, pos::PlayerPos::P1);
+    }
+
+    #[test]
+    fn test_all_trump_play() {
+        use cards::{Card, Rank, Suit, Trump};
+
+        let hand = |cards: &[(Suit, Rank)]| {
+            let mut hand = cards::Hand::new();
+            for &(suit, rank) in cards {
+                hand.add(Card::new(suit, rank));
+            }
+            hand
+        };
+
+        let mut trick = trick::Trick::new(pos::PlayerPos::P0);
+        trick.play_card(
+            pos::PlayerPos::P0,
+            Card::new(Suit::Heart, Rank::RankK),
+            Trump::AllTrump,
+        );
+
+        // Every suit must be raised when possible.
+        let rules = rules::RuleSet::default();
+        let following = hand(&[
+            (Suit::Heart, Rank::Rank7),
+            (Suit::Heart, Rank::RankA),
+            (Suit::Spade, Rank::Rank9),
+        ]);
         assert_eq!(
-            game.play_card(
+            legal_cards_with(
                 pos::PlayerPos::P1,
-                cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7)
-            )
-            .err(),
-            Some(PlayError::CardMissing)
+                following,
+                &trick,
+                Trump::AllTrump,
+                &rules
+            ),
+            hand(&[(Suit::Heart, Rank::RankA)])
         );
-        // Wrong color
+
+        // Without a higher card, any card of the suit will do.
+        let low = hand(&[
+            (Suit::Heart, Rank::Rank7),
+            (Suit::Heart, Rank::Rank8),
+            (Suit::Spade, Rank::Rank9),
+        ]);
         assert_eq!(
-            game.play_card(
-                pos::PlayerPos::P1,
-                cards::Card::new(cards::Suit::Spade, cards::Rank::Rank7)
-            )
-            .err(),
-            Some(PlayError::IncorrectSuit)
+            legal_cards_with(pos::PlayerPos::P1, low, &trick, Trump::AllTrump, &rules),
+            hand(&[(Suit::Heart, Rank::Rank7), (Suit::Heart, Rank::Rank8)])
         );
+
+        // Without the suit, there is nothing to trump with: any card will do.
+        let void = hand(&[(Suit::Spade, Rank::RankJ), (Suit::Club, Rank::Rank8)]);
         assert_eq!(
-            game.play_card(
-                pos::PlayerPos::P1,
-                cards::Card::new(cards::Suit::Club, cards::Rank::RankQ)
-            )
-            .ok(),
-            Some(TrickResult::Nothing)
+            legal_cards_with(pos::PlayerPos::P1, void, &trick, Trump::AllTrump, &rules),
+            void
         );
-        // Invalid piss
+
+        // The partner must raise too, unless the rules say otherwise.
+        trick.play_card(
+            pos::PlayerPos::P1,
+            Card::new(Suit::Club, Rank::Rank8),
+            Trump::AllTrump,
+        );
+        let partner = hand(&[(Suit::Heart, Rank::RankQ), (Suit::Heart, Rank::Rank9)]);
         assert_eq!(
-            game.play_card(
-                pos::PlayerPos::P2,
-                cards::Card::new(cards::Suit::Diamond, cards::Rank::Rank7)
-            )
-            .err(),
-            Some(PlayError::InvalidPiss)
+            legal_cards_with(pos::PlayerPos::P2, partner, &trick, Trump::AllTrump, &rules),
+            hand(&[(Suit::Heart, Rank::Rank9)])
         );
+        let relaxed = rules::RuleSet {
+            no_overtrump_on_partner: true,
+            ..rules
+        };
         assert_eq!(
-            game.play_card(
+            legal_cards_with(
                 pos::PlayerPos::P2,
-                cards::Card::new(cards::Suit::Heart, cards::Rank::RankQ)
-            )
-            .ok(),
-            Some(TrickResult::Nothing)
+                partner,
+                &trick,
+                Trump::AllTrump,
+                &relaxed
+            ),
+            partner
         );
-        // UnderTrump
-        assert_eq!(
-            game.play_card(
-                pos::PlayerPos::P3,
-                cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7)
-            )
-            .err(),
-            Some(PlayError::NonRaisedTrump)
+
+        // The nine beats the ace.
+        trick.play_card(
+            pos::PlayerPos::P2,
+            Card::new(Suit::Heart, Rank::Rank9),
+            Trump::AllTrump,
         );
-        assert_eq!(
-            game.play_card(
-                pos::PlayerPos::P3,
-                cards::Card::new(cards::Suit::Heart, cards::Rank::RankJ)
-            )
-            .ok(),
-            Some(TrickResult::TrickOver(
-                pos::PlayerPos::P3,
-                game.get_game_result()
-            ))
+        trick.play_card(
+            pos::PlayerPos::P3,
+            Card::new(Suit::Heart, Rank::RankA),
+            Trump::AllTrump,
         );
+        assert_eq!(trick.winner, pos::PlayerPos::P2);
+    }
+
+    #[test]
+    fn test_builder() {
+        let hands = crate::deal_seeded_hands([6; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P2, "100♦").unwrap();
+        let mut game = GameState::new(pos::PlayerPos::P0, hands, contract.clone());
+        for _ in 0..22 {
+            play_first_legal(&mut game);
+        }
+
+        let mut builder = GameStateBuilder::new(pos::PlayerPos::P0, game.hands(), contract.clone());
+        for &trick in game.tricks() {
+            builder = builder.trick(trick);
+        }
+        let current = *game.current_trick();
+        let mut built = builder.clone().current_trick(current).build().unwrap();
+        assert_eq!(built.hash(), game.hash());
+        assert_eq!(built.points(), game.points());
+        assert_eq!(built.next_player(), game.next_player());
+        while !built.is_over() {
+            play_first_legal(&mut built);
+        }
+
+        // Custom points must keep the total.
+        let total = game.points()[0] + game.points()[1];
+        let custom = builder.clone().current_trick(current).points([total, 0]);
+        assert_eq!(custom.build().unwrap().points(), [total, 0]);
+        let custom = builder.clone().current_trick(current).points([total, 1]);
+        assert!(custom.build().is_err());
+
+        // The cards on the table are missing from the deal.
+        assert!(builder.build().is_err());
+
+        // The first trick was not led by P1.
+        let wrong = GameStateBuilder::new(pos::PlayerPos::P1, game.hands(), contract)
+            .trick(game.tricks()[0]);
+        assert!(wrong.build().is_err());
     }
 
     #[test]
@@ -492,13 +3940,13 @@ mod tests {
     }
 }
 
-#[cfg(feature = "use_bench")]
+#[cfg(all(test, feature = "use_bench"))]
 mod benchs {
-    use deal_seeded_hands;
+    use crate::deal_seeded_hands;
     use test::Bencher;
 
     use super::*;
-    use {bid, cards, pos};
+    use crate::{bid, cards, pos};
 
     #[bench]
     fn bench_can_play(b: &mut Bencher) {
@@ -506,29 +3954,26 @@ mod benchs {
             let player = game.next_player();
             for c in game.hands()[player as usize].list() {
                 let mut new_game = game.clone();
-                match new_game.play_card(player, c) {
-                    Ok(_) => {
-                        if depth > 0 {
-                            try_deeper(&new_game, depth - 1);
-                        }
-                    }
-                    _ => (),
-                };
+                if new_game.play_card(player, c).is_ok() && depth > 0 {
+                    try_deeper(&new_game, depth - 1);
+                }
             }
         }
 
-        let seed = &[3, 32, 654, 1, 844];
+        let seed = [3; 32];
         let hands = deal_seeded_hands(seed);
         let game = GameState::new(
             pos::PlayerPos::P0,
             hands,
             bid::Contract {
                 author: pos::PlayerPos::P0,
-                trump: cards::Suit::Heart,
+                trump: cards::Trump::Suit(cards::Suit::Heart),
                 target: bid::Target::Contract80,
                 coinche_level: 0,
+                raises: Vec::new(),
             },
-        );
+        )
+        .with_events(false);
         b.iter(|| try_deeper(&game, 4));
     }
 }
@@ -19,17 +19,20 @@
 //!     let hands = auction.hands();
 //!
 //!     // Players bid or pass
-//!     auction.bid(pos::PlayerPos::P0, cards::Suit::Heart, bid::Target::Contract80).unwrap();
+//!     auction.bid(pos::PlayerPos::P0, cards::Suit::Heart.into(), bid::Target::Contract80).unwrap();
 //!     auction.pass(pos::PlayerPos::P1).unwrap();
 //!     auction.pass(pos::PlayerPos::P2).unwrap();
 //!     // The result is `Over` when the auction is ready to complete
 //!     match auction.pass(pos::PlayerPos::P3) {
-//!         Ok(bid::AuctionState::Over) => (),
+//!         Ok(update) if update.state == bid::AuctionState::Over => (),
 //!         _ => panic!("Should not happen"),
 //!     };
 //!
 //!     // Complete the auction to enter the second phase
-//!     let mut game = auction.complete().unwrap();
+//!     let mut game = match auction.complete() {
+//!         Ok(game) => game,
+//!         Err((_, err)) => panic!("{}", err),
+//!     };
 //!
 //!     // Play some cards
 //!     game.play_card(pos::PlayerPos::P0, hands[0].get_card());
@@ -37,11 +40,38 @@
 //! }
 //! ```
 
+#![cfg_attr(all(test, feature = "use_bench"), feature(test))]
+
+#[cfg(all(test, feature = "use_bench"))]
+extern crate test;
+
+// Emits a `tracing` event when the `tracing` feature is enabled, and nothing otherwise.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub mod announces;
 pub mod bid;
 pub mod cards;
+pub mod deal;
 pub mod game;
+#[cfg(feature = "mcts")]
+pub mod mcts;
+pub mod narration;
+pub mod player;
 pub mod points;
 pub mod pos;
+pub mod puzzle;
+#[cfg(feature = "cli")]
+pub mod render;
+pub mod rules;
+pub mod score;
+pub mod session;
+pub mod solver;
+pub mod tracking;
 pub mod trick;
 
 // Expose the module or their content directly? Still unsure.
@@ -83,6 +113,37 @@ pub fn deal_seeded_hands(seed: [u8; 32]) -> [cards::Hand; 4] {
     hands
 }
 
+// Deals every heart to P0, spades to P1, clubs to P2 and diamonds to P3.
+#[cfg(test)]
+pub(crate) fn suit_hands() -> [cards::Hand; 4] {
+    let suits = [
+        cards::Suit::Heart,
+        cards::Suit::Spade,
+        cards::Suit::Club,
+        cards::Suit::Diamond,
+    ];
+    let mut hands = [cards::Hand::new(); 4];
+    for (hand, &suit) in hands.iter_mut().zip(suits.iter()) {
+        for rank in cards::Rank::by_plain_strength() {
+            hand.add(cards::Card::new(suit, rank));
+        }
+    }
+    hands
+}
+
+// Swaps card `a`, held by `p`, with card `b`, held by `q`.
+#[cfg(test)]
+pub(crate) fn swap_cards(
+    hands: &mut [cards::Hand; 4],
+    (p, a): (pos::PlayerPos, cards::Card),
+    (q, b): (pos::PlayerPos, cards::Card),
+) {
+    hands[p as usize].remove(a);
+    hands[p as usize].add(b);
+    hands[q as usize].remove(b);
+    hands[q as usize].add(a);
+}
+
 #[test]
 fn test_deals() {
     let hands = deal_hands();
@@ -0,0 +1,375 @@
+//! Information-set Monte Carlo tree search (ISMCTS) player.
+//!
+//! Each iteration deals the unseen cards at random, consistently with what the player
+//! knows, then walks down a single tree shared by every such deal.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::bid;
+use super::cards;
+use super::game;
+use super::player;
+use super::points;
+use super::pos;
+use super::tracking;
+
+/// How long to search before choosing a card.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Budget {
+    /// Run this many iterations.
+    Iterations(usize),
+    /// Run as many iterations as possible in the given time.
+    Time(Duration),
+}
+
+/// Default exploration constant, balancing good moves against rarely tried ones.
+pub const DEFAULT_EXPLORATION: f64 = 0.7;
+
+// A card played in the search tree.
+struct Node {
+    // Card leading to this node, and its player. `None` for the root.
+    card: Option<cards::Card>,
+    player: pos::PlayerPos,
+    children: Vec<usize>,
+    visits: u32,
+    // Number of iterations where this card was legal.
+    available: u32,
+    reward: f64,
+}
+
+impl Node {
+    fn new(card: Option<cards::Card>, player: pos::PlayerPos) -> Self {
+        Node {
+            card,
+            player,
+            children: Vec::new(),
+            visits: 0,
+            available: 1,
+            reward: 0.0,
+        }
+    }
+
+    fn ucb(&self, exploration: f64) -> f64 {
+        let visits = f64::from(self.visits);
+        self.reward / visits + exploration * (f64::from(self.available).ln() / visits).sqrt()
+    }
+}
+
+/// Chooses a card for the player of `view`, searching with the given budget.
+///
+/// `exploration` is the UCB constant (see `DEFAULT_EXPLORATION`).
+pub fn search<R: Rng + ?Sized>(
+    view: &game::PlayerView,
+    budget: Budget,
+    exploration: f64,
+    rng: &mut R,
+) -> cards::Card {
+    let legal = view.legal_moves.list();
+    if legal.len() == 1 {
+        return legal[0];
+    }
+
+    let mut played = view.tricks.clone();
+    played.push(view.current_trick);
+    let mut sampler = tracking::Knowledge::from_tricks(
+        view.pos,
+        view.hand,
+        &played,
+        view.contract.trump,
+        view.rules,
+    )
+    .sampler();
+
+    let mut tree = vec![Node::new(None, view.pos)];
+    let start = Instant::now();
+    let mut iterations = 0;
+    loop {
+        let done = match budget {
+            Budget::Iterations(n) => iterations >= n,
+            Budget::Time(duration) => iterations > 0 && start.elapsed() >= duration,
+        };
+        if done {
+            break;
+        }
+        iterations += 1;
+
+        if let Some(game) = determinize(view, &mut sampler, rng) {
+            iterate(&mut tree, game, exploration, rng);
+        }
+    }
+
+    // The most visited card is the most reliable one.
+    tree[0]
+        .children
+        .iter()
+        .map(|&c| &tree[c])
+        .max_by_key(|node| node.visits)
+        .and_then(|node| node.card)
+        .unwrap_or(legal[0])
+}
+
+// Runs a single iteration on a determinized game.
+fn iterate<R: Rng + ?Sized>(
+    tree: &mut Vec<Node>,
+    mut game: game::GameState,
+    exploration: f64,
+    rng: &mut R,
+) {
+    let mut path = vec![0];
+    let mut node = 0;
+    while !game.is_over() {
+        let player = game.next_player();
+        let legal = game.legal_moves(player).unwrap();
+
+        let mut tried = cards::Hand::new();
+        let mut children = Vec::new();
+        for &c in &tree[node].children {
+            let card = tree[c].card.unwrap();
+            tried.add(card);
+            if legal.has(card) {
+                children.push(c);
+            }
+        }
+        for &c in &children {
+            tree[c].available += 1;
+        }
+
+        let untried = legal.difference(tried).list();
+        let (child, expanded) = match untried.choose(rng) {
+            Some(&card) => {
+                tree.push(Node::new(Some(card), player));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                (child, true)
+            }
+            None => {
+                let child = *children
+                    .iter()
+                    .max_by(|&&a, &&b| {
+                        let a = tree[a].ucb(exploration);
+                        let b = tree[b].ucb(exploration);
+                        a.partial_cmp(&b).unwrap()
+                    })
+                    .unwrap();
+                (child, false)
+            }
+        };
+
+        game.play_inner(player, tree[child].card.unwrap(), None)
+            .unwrap();
+        path.push(child);
+        node = child;
+        if expanded {
+            break;
+        }
+    }
+
+    let result = game.playout(rng);
+    for &n in &path {
+        let team = tree[n].player.team();
+        tree[n].visits += 1;
+        tree[n].reward += reward(&result, team);
+    }
+}
+
+// Scores a result for `team`, between 0 and 1: half for winning, half for the points made.
+fn reward(result: &game::GameResult, team: pos::Team) -> f64 {
+    match *result {
+        game::GameResult::GameOver {
+            winners, points, ..
+        } => {
+            let total = (points[0] + points[1]).max(1);
+            let share = f64::from(points[team as usize]) / f64::from(total);
+            let won = if winners == team { 1.0 } else { 0.0 };
+            (won + share) / 2.0
+        }
+        game::GameResult::Nothing => 0.5,
+    }
+}
+
+// Deals the unseen cards at random with `sampler`, and rebuilds the game.
+fn determinize<R: Rng + ?Sized>(
+    view: &game::PlayerView,
+    sampler: &mut tracking::Sampler,
+    rng: &mut R,
+) -> Option<game::GameState> {
+    let hands = sampler.sample(rng)?;
+    let first = view
+        .tricks
+        .first()
+        .map_or(view.current_trick.first, |trick| trick.first);
+    let mut builder = game::GameStateBuilder::new(first, hands, view.contract.clone())
+        .rules(view.rules)
+        .current_trick(view.current_trick);
+    for &trick in &view.tricks {
+        builder = builder.trick(trick);
+    }
+    builder.build().ok().map(|game| game.with_events(false))
+}
+
+/// A player searching card play with ISMCTS.
+///
+/// Bidding is not searched: it takes 80 in its best trump suit when it is strong enough.
+pub struct MctsPlayer {
+    budget: Budget,
+    exploration: f64,
+    rng: RefCell<StdRng>,
+}
+
+impl MctsPlayer {
+    /// Creates a player searching with the given budget, seeded from the system entropy.
+    pub fn new(budget: Budget) -> Self {
+        MctsPlayer {
+            budget,
+            exploration: DEFAULT_EXPLORATION,
+            rng: RefCell::new(StdRng::from_entropy()),
+        }
+    }
+
+    /// Creates a player searching with the given budget, with a deterministic seed.
+    pub fn seeded(budget: Budget, seed: u64) -> Self {
+        MctsPlayer {
+            budget,
+            exploration: DEFAULT_EXPLORATION,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Sets the exploration constant.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+}
+
+impl player::Player for MctsPlayer {
+    fn bid(&self, view: &bid::AuctionView) -> bid::BidAction {
+        let best = (0..4)
+            .map(|n| cards::Trump::from(cards::Suit::from_n(n)))
+            .max_by_key(|&trump| points::hand_trump_score(view.hand, trump))
+            .unwrap();
+        let opening = (best, bid::Target::Contract80);
+        if points::hand_trump_score(view.hand, best) >= 40
+            && view.legal_bids.bids.contains(&opening)
+        {
+            bid::BidAction::Bid(best, bid::Target::Contract80)
+        } else {
+            bid::BidAction::Pass
+        }
+    }
+
+    fn play(&self, view: &game::PlayerView) -> cards::Card {
+        search(
+            view,
+            self.budget,
+            self.exploration,
+            &mut *self.rng.borrow_mut(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::Player;
+    use crate::{bid, cards, game, player, pos};
+
+    #[test]
+    fn test_search() {
+        let mcts = MctsPlayer::seeded(Budget::Iterations(40), 3);
+        let players: [&dyn Player; 4] = [&mcts; 4];
+        let mut auction = bid::Auction::new_seeded(pos::PlayerPos::P0, 3);
+        auction
+            .bid(
+                pos::PlayerPos::P0,
+                cards::Suit::Heart.into(),
+                bid::Target::Contract80,
+            )
+            .unwrap();
+        for p in pos::PlayerPos::P1.until_n(3) {
+            auction.pass(p).unwrap();
+        }
+        let mut game = auction.complete().unwrap();
+        player::run_game(&players, &mut game).unwrap();
+        assert!(game.is_over());
+
+        // A time budget runs at least one iteration.
+        let mut game = game::GameState::new(
+            pos::PlayerPos::P0,
+            crate::deal_seeded_hands([5; 32]),
+            bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap(),
+        );
+        let view = game.view_for(pos::PlayerPos::P0);
+        let mut rng = StdRng::seed_from_u64(5);
+        let card = search(&view, Budget::Time(Duration::from_millis(0)), 1.0, &mut rng);
+        assert!(view.legal_moves.has(card));
+        game.play_card(pos::PlayerPos::P0, card).unwrap();
+    }
+
+    #[test]
+    fn test_search_finds_winner() {
+        use cards::{Card, Rank, Suit};
+
+        // Two tricks left: P0 must cash the ace of hearts before leading the seven.
+        let hands = [
+            [(Suit::Heart, Rank::RankA), (Suit::Heart, Rank::Rank7)],
+            [(Suit::Heart, Rank::RankK), (Suit::Club, Rank::Rank8)],
+            [(Suit::Diamond, Rank::Rank7), (Suit::Diamond, Rank::Rank8)],
+            [(Suit::Heart, Rank::RankQ), (Suit::Club, Rank::Rank9)],
+        ];
+        let mut position = [cards::Hand::new(); 4];
+        let mut remaining = cards::Hand::new();
+        for id in 0..32 {
+            remaining.add(Card::from_id(id));
+        }
+        for (hand, cards) in position.iter_mut().zip(hands.iter()) {
+            for &(suit, rank) in cards {
+                hand.add(Card::new(suit, rank));
+            }
+            remaining = remaining.difference(*hand);
+        }
+
+        // P0 won the first six tricks with their trumps.
+        let trump = cards::Trump::Suit(Suit::Spade);
+        let mut trumps = [
+            Rank::RankJ,
+            Rank::Rank9,
+            Rank::RankA,
+            Rank::RankX,
+            Rank::RankK,
+            Rank::RankQ,
+        ]
+        .iter()
+        .map(|&rank| Card::new(Suit::Spade, rank))
+        .collect::<Vec<_>>()
+        .into_iter();
+        for card in trumps.clone() {
+            remaining.remove(card);
+        }
+        let mut others = remaining.list().into_iter();
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap();
+        let mut builder = game::GameStateBuilder::new(pos::PlayerPos::P0, position, contract);
+        for _ in 0..6 {
+            let mut trick = crate::trick::Trick::new(pos::PlayerPos::P0);
+            trick.play_card(pos::PlayerPos::P0, trumps.next().unwrap(), trump);
+            for p in pos::PlayerPos::P1.until_n(3) {
+                trick.play_card(p, others.next().unwrap(), trump);
+            }
+            builder = builder.trick(trick);
+        }
+        let game = builder.build().unwrap();
+
+        let view = game.view_for(pos::PlayerPos::P0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let card = search(
+            &view,
+            Budget::Iterations(200),
+            DEFAULT_EXPLORATION,
+            &mut rng,
+        );
+        assert_eq!(card, Card::new(Suit::Heart, Rank::RankA));
+    }
+}
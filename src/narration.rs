@@ -0,0 +1,174 @@
+//! Describe game events with plain sentences, for screen readers and text-only clients.
+
+use super::cards;
+use super::game;
+use super::pos;
+use super::trick;
+
+/// Turns game events into sentences.
+///
+/// It follows the tricks from the events alone, to tell when a player takes the lead.
+#[derive(Clone, Debug)]
+pub struct Narrator {
+    trump: cards::Trump,
+    // Trick being played, once its first card was seen.
+    trick: Option<trick::Trick>,
+}
+
+impl Narrator {
+    /// Creates a narrator for a deal played with the given trump.
+    pub fn new(trump: cards::Trump) -> Self {
+        Narrator { trump, trick: None }
+    }
+
+    /// Describes a single event, which must come in the order of the event log.
+    pub fn narrate(&mut self, event: &game::GameEvent) -> String {
+        match *event {
+            game::GameEvent::CardPlayed { pos, card, .. } => {
+                let trick = self.trick.get_or_insert_with(|| trick::Trick::new(pos));
+                let previous = trick.winner;
+                trick.play_card(pos, card, self.trump);
+
+                let seat = pos.seat();
+                if pos == trick.first {
+                    format!("{} leads the {}.", seat, card_name(card))
+                } else if trick.winner == pos && previous != pos {
+                    format!("{} plays the {} and takes the lead.", seat, card_name(card))
+                } else {
+                    format!("{} plays the {}.", seat, card_name(card))
+                }
+            }
+            game::GameEvent::TrickWon { winner, points, .. } => {
+                self.trick = Some(trick::Trick::new(winner));
+                format!(
+                    "{} win the trick, {} points.",
+                    team_name(winner.team()),
+                    points
+                )
+            }
+            game::GameEvent::BeloteAnnounced { pos, rebelote } => {
+                let word = if rebelote { "rebelote" } else { "belote" };
+                format!("{} announces {}.", pos.seat(), word)
+            }
+            game::GameEvent::GameEnded(ref result) => narrate_result(result),
+        }
+    }
+
+    /// Describes every event, in order.
+    pub fn narrate_all(&mut self, events: &[game::GameEvent]) -> Vec<String> {
+        events.iter().map(|event| self.narrate(event)).collect()
+    }
+}
+
+fn narrate_result(result: &game::GameResult) -> String {
+    match *result {
+        game::GameResult::Nothing => "The deal goes on.".to_string(),
+        game::GameResult::GameOver {
+            scores,
+            fulfilled,
+            conceded,
+            ..
+        } => {
+            let mut sentence = String::new();
+            if let Some(team) = conceded {
+                sentence.push_str(&format!("{} concede the deal. ", team_name(team)));
+            }
+            let outcome = if fulfilled { "fulfilled" } else { "defeated" };
+            sentence.push_str(&format!(
+                "The contract is {}: {} score {}, {} score {}.",
+                outcome,
+                team_name(pos::Team::T02),
+                scores[0],
+                team_name(pos::Team::T13),
+                scores[1]
+            ));
+            sentence
+        }
+    }
+}
+
+fn team_name(team: pos::Team) -> &'static str {
+    match team {
+        pos::Team::T02 => "North-South",
+        pos::Team::T13 => "East-West",
+    }
+}
+
+fn card_name(card: cards::Card) -> String {
+    let rank = match card.rank() {
+        cards::Rank::Rank7 => "seven",
+        cards::Rank::Rank8 => "eight",
+        cards::Rank::Rank9 => "nine",
+        cards::Rank::RankX => "ten",
+        cards::Rank::RankJ => "jack",
+        cards::Rank::RankQ => "queen",
+        cards::Rank::RankK => "king",
+        cards::Rank::RankA => "ace",
+    };
+    let suit = match card.suit() {
+        cards::Suit::Heart => "hearts",
+        cards::Suit::Spade => "spades",
+        cards::Suit::Diamond => "diamonds",
+        cards::Suit::Club => "clubs",
+    };
+    format!("{} of {}", rank, suit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, game, pos};
+
+    #[test]
+    fn test_narrator() {
+        use cards::{Card, Rank, Suit};
+
+        // Each player holds a full suit, but P0 and P1 swapped a card.
+        let mut hands = crate::suit_hands();
+        crate::swap_cards(
+            &mut hands,
+            (pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::RankA)),
+            (pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank7)),
+        );
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♠").unwrap();
+        let mut game = game::GameState::new(pos::PlayerPos::P0, hands, contract);
+
+        let plays = [
+            (pos::PlayerPos::P0, Card::new(Suit::Spade, Rank::Rank7)),
+            (pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::RankJ)),
+            (pos::PlayerPos::P2, Card::new(Suit::Club, Rank::Rank7)),
+            (pos::PlayerPos::P3, Card::new(Suit::Diamond, Rank::Rank7)),
+        ];
+        for &(p, card) in plays.iter() {
+            game.play_card(p, card).unwrap();
+        }
+
+        let mut narrator = Narrator::new(game.contract().trump);
+        assert_eq!(
+            narrator.narrate_all(&game.drain_events()),
+            vec![
+                "North leads the seven of spades.",
+                "East plays the jack of spades and takes the lead.",
+                "South plays the seven of clubs.",
+                "West plays the seven of diamonds.",
+                "East-West win the trick, 20 points.",
+            ]
+        );
+
+        // The next trick is led by the winner.
+        game.play_card(pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank9))
+            .unwrap();
+        assert_eq!(
+            narrator.narrate_all(&game.drain_events()),
+            vec!["East leads the nine of spades."]
+        );
+
+        // East-West also keep their belote.
+        let result = game.concede(pos::Team::T02).unwrap();
+        assert_eq!(
+            narrator.narrate(&game::GameEvent::GameEnded(result)),
+            "North-South concede the deal. \
+             The contract is defeated: North-South score 0, East-West score 180."
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Plug players (bots or remote clients) into the crate, and let them play whole deals.
+
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::bid;
+use super::cards;
+use super::deal;
+use super::game;
+use super::pos;
+use super::session;
+
+/// Something that takes decisions for a seat.
+pub trait Player {
+    /// Chooses an action during the auction.
+    ///
+    /// Passing also declines a last chance to coinche.
+    fn bid(&self, view: &bid::AuctionView) -> bid::BidAction;
+
+    /// Chooses a card to play.
+    fn play(&self, view: &game::PlayerView) -> cards::Card;
+}
+
+/// Plays at random, choosing uniformly among the legal actions.
+///
+/// It serves as a baseline opponent, and for Monte Carlo playouts.
+pub struct RandomPlayer {
+    rng: RefCell<StdRng>,
+}
+
+impl RandomPlayer {
+    /// Creates a random player, seeded from the system entropy.
+    pub fn new() -> Self {
+        RandomPlayer {
+            rng: RefCell::new(StdRng::from_entropy()),
+        }
+    }
+
+    /// Creates a random player with a deterministic seed.
+    pub fn seeded(seed: u64) -> Self {
+        RandomPlayer {
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl Default for RandomPlayer {
+    fn default() -> Self {
+        RandomPlayer::new()
+    }
+}
+
+impl Player for RandomPlayer {
+    fn bid(&self, view: &bid::AuctionView) -> bid::BidAction {
+        // A player asked to speak may always pass, if only to decline a last chance to coinche.
+        let mut actions = vec![bid::BidAction::Pass];
+        if view.legal_bids.can_coinche {
+            actions.push(bid::BidAction::Coinche);
+        }
+        for &(trump, target) in &view.legal_bids.bids {
+            actions.push(bid::BidAction::Bid(trump, target));
+        }
+        *actions.choose(&mut *self.rng.borrow_mut()).unwrap()
+    }
+
+    fn play(&self, view: &game::PlayerView) -> cards::Card {
+        *view
+            .legal_moves
+            .list()
+            .choose(&mut *self.rng.borrow_mut())
+            .unwrap()
+    }
+}
+
+/// Runs the auction until it is over, asking each player for their action.
+///
+/// Returns the first illegal action, if any.
+pub fn run_auction(
+    players: &[&dyn Player; 4],
+    auction: &mut bid::Auction,
+) -> Result<(), bid::BidError> {
+    while let Some(pos) = auction.next_to_speak() {
+        let action = players[pos as usize].bid(&auction.view_for(pos));
+        auction.speak(pos, action)?;
+    }
+    Ok(())
+}
+
+/// Plays the game until it is over, asking each player for their card.
+///
+/// Returns the first illegal card, if any.
+pub fn run_game(
+    players: &[&dyn Player; 4],
+    game: &mut game::GameState,
+) -> Result<(), game::PlayError> {
+    while !game.is_over() {
+        let pos = game.next_player();
+        let card = players[pos as usize].play(&game.view_for(pos));
+        game.play_card(pos, card)?;
+    }
+    Ok(())
+}
+
+/// Runs a whole deal: the auction, then the game.
+///
+/// Returns the finished game, or `None` if every player passed.
+pub fn play_deal(
+    players: &[&dyn Player; 4],
+    auction: bid::Auction,
+) -> Result<Option<game::GameState>, deal::GameError> {
+    let mut deal = deal::Game::from(auction);
+    while let Some(pos) = deal.next_player() {
+        let player = players[pos as usize];
+        let action = match deal {
+            deal::Game::Bidding(ref auction) => player.bid(&auction.view_for(pos)).into(),
+            deal::Game::Playing(ref game) => player.play(&game.view_for(pos)).into(),
+            deal::Game::Cancelled(_) | deal::Game::Done(..) => unreachable!(),
+        };
+        deal.apply(pos, action)?;
+    }
+    match deal {
+        deal::Game::Done(game, _) => Ok(Some(game)),
+        _ => Ok(None),
+    }
+}
+
+/// Number of deals in a row that may be cancelled before `play_match` gives up.
+pub const MAX_CANCELLED_DEALS: usize = 100;
+
+/// Plays deals until the match is over.
+///
+/// Returns the team that won the match, or `MatchError::Stalled` if every player
+/// passed `MAX_CANCELLED_DEALS` deals in a row.
+pub fn play_match(
+    players: &[&dyn Player; 4],
+    partie: &mut session::Match,
+) -> Result<pos::Team, deal::GameError> {
+    let mut cancelled = 0;
+    loop {
+        if let Some(team) = partie.winner() {
+            return Ok(team);
+        }
+
+        let mut auction = partie.new_auction();
+        run_auction(players, &mut auction)?;
+        match partie.end_auction(auction).map_err(|(_, err)| err)? {
+            Some(mut game) => {
+                cancelled = 0;
+                run_game(players, &mut game)?;
+                partie.end_deal(&game)?;
+            }
+            None => {
+                cancelled += 1;
+                if cancelled == MAX_CANCELLED_DEALS {
+                    return Err(session::MatchError::Stalled.into());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, game, pos, session};
+
+    // Bids 80 in hearts when first to speak, and plays the first legal card.
+    struct Simple;
+
+    impl Player for Simple {
+        fn bid(&self, view: &bid::AuctionView) -> bid::BidAction {
+            if view.history.is_empty() {
+                bid::BidAction::Bid(cards::Suit::Heart.into(), bid::Target::Contract80)
+            } else {
+                bid::BidAction::Pass
+            }
+        }
+
+        fn play(&self, view: &game::PlayerView) -> cards::Card {
+            view.legal_moves.get_card()
+        }
+    }
+
+    // Always passes, and plays its highest card even when it is not allowed.
+    struct Cheater;
+
+    impl Player for Cheater {
+        fn bid(&self, _: &bid::AuctionView) -> bid::BidAction {
+            bid::BidAction::Pass
+        }
+
+        fn play(&self, view: &game::PlayerView) -> cards::Card {
+            *view.hand.list().last().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_play_deal() {
+        let simple = Simple;
+        let players: [&dyn Player; 4] = [&simple; 4];
+        let auction = bid::Auction::new_seeded(pos::PlayerPos::P0, 42);
+        let game = play_deal(&players, auction).unwrap().unwrap();
+        assert!(game.is_over());
+        assert_eq!(game.contract().author, pos::PlayerPos::P0);
+
+        let cheater = Cheater;
+        let players: [&dyn Player; 4] = [&cheater; 4];
+        let auction = bid::Auction::new_seeded(pos::PlayerPos::P0, 42);
+        assert!(play_deal(&players, auction).unwrap().is_none());
+
+        // The cheater breaks the rules at some point.
+        let players: [&dyn Player; 4] = [&simple, &cheater, &simple, &simple];
+        let auction = bid::Auction::new_seeded(pos::PlayerPos::P0, 42);
+        assert!(matches!(
+            play_deal(&players, auction),
+            Err(deal::GameError::Play(_))
+        ));
+    }
+
+    #[test]
+    fn test_random_player() {
+        for seed in 0..20 {
+            let players = [
+                RandomPlayer::seeded(seed),
+                RandomPlayer::seeded(seed + 100),
+                RandomPlayer::seeded(seed + 200),
+                RandomPlayer::seeded(seed + 300),
+            ];
+            let players: [&dyn Player; 4] = [&players[0], &players[1], &players[2], &players[3]];
+            let auction = bid::Auction::new_seeded(pos::PlayerPos::P0, seed);
+            if let Some(game) = play_deal(&players, auction).unwrap() {
+                assert!(game.is_over());
+            }
+        }
+
+        // The same seeds replay the same deal.
+        let play = || {
+            let player = RandomPlayer::seeded(7);
+            let players: [&dyn Player; 4] = [&player; 4];
+            let auction = bid::Auction::new_seeded(pos::PlayerPos::P0, 7);
+            play_deal(&players, auction)
+                .unwrap()
+                .map(|game| game.checksum())
+        };
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn test_play_match() {
+        let simple = Simple;
+        let players: [&dyn Player; 4] = [&simple; 4];
+        let mut partie = session::Match::new(pos::PlayerPos::P0, 500);
+        let winner = play_match(&players, &mut partie).unwrap();
+        assert_eq!(partie.winner(), Some(winner));
+        assert!(partie.sheet().deals().len() >= 2);
+
+        // Nobody ever bids.
+        let cheater = Cheater;
+        let players: [&dyn Player; 4] = [&cheater; 4];
+        let mut partie = session::Match::new(pos::PlayerPos::P0, 500);
+        assert_eq!(
+            play_match(&players, &mut partie),
+            Err(deal::GameError::Match(session::MatchError::Stalled))
+        );
+        assert_eq!(partie.cancelled(), MAX_CANCELLED_DEALS);
+    }
+}
@@ -2,83 +2,500 @@
 
 use super::cards;
 
-/// Returns the number of points `card` is worth, with the current trump suit.
-pub fn score(card: cards::Card, trump: cards::Suit) -> i32 {
+/// Total value of the cards in a deal, with the classic scoring.
+pub const CARDS_TOTAL: i32 = 152;
+
+/// Total points of a deal with the classic scoring, including the dix de der.
+pub const DEAL_TOTAL: i32 = 162;
+
+/// Bonus for holding the King and Queen of trump (belote and rebelote).
+pub const BELOTE_SCORE: i32 = 20;
+
+/// Defines how many points cards and bonuses are worth.
+///
+/// Every method defaults to the classic rules, so a house variant only needs
+/// to override the values it changes.
+pub trait ScoringScheme {
+    /// Returns the score for the given rank when it is the trump.
+    fn trump_score(&self, rank: cards::Rank) -> i32 {
+        trump_score(rank)
+    }
+
+    /// Returns the score for the given rank when it is not the trump.
+    fn usual_score(&self, rank: cards::Rank) -> i32 {
+        usual_score(rank)
+    }
+
+    /// Returns the number of points `card` is worth, with the current trump mode.
+    fn score(&self, card: cards::Card, trump: cards::Trump) -> i32 {
+        let r = card.rank();
+        match trump {
+            cards::Trump::Suit(suit) if suit == card.suit() => self.trump_score(r),
+            cards::Trump::Suit(_) => self.usual_score(r),
+            cards::Trump::NoTrump => self.no_trump_score(r),
+            cards::Trump::AllTrump => self.all_trump_score(r),
+        }
+    }
+
+    /// Returns the total points of a deal with the given trump mode, including the dix de der.
+    fn deal_total(&self, trump: cards::Trump) -> i32 {
+        match trump {
+            cards::Trump::Suit(_) => {
+                (0..8)
+                    .map(cards::Rank::from_n)
+                    .map(|r| self.trump_score(r) + 3 * self.usual_score(r))
+                    .sum::<i32>()
+                    + self.last_trick_bonus()
+            }
+            cards::Trump::NoTrump => self.no_trump_total(),
+            cards::Trump::AllTrump => self.all_trump_total(),
+        }
+    }
+
+    /// Returns the bonus for winning the last trick (dix de der).
+    fn last_trick_bonus(&self) -> i32 {
+        10
+    }
+
+    /// Returns how trick points are rounded before being written on the score sheet.
+    fn rounding(&self) -> RoundingRule {
+        RoundingRule::Exact
+    }
+
+    /// Returns the score for the given rank in a no-trump (Sans-Atout) game.
+    fn no_trump_score(&self, rank: cards::Rank) -> i32 {
+        no_trump_score(rank)
+    }
+
+    /// Returns the score for the given rank in an all-trump (Tout-Atout) game.
+    fn all_trump_score(&self, rank: cards::Rank) -> i32 {
+        all_trump_score(rank)
+    }
+
+    /// Returns how points are normalized in no-trump and all-trump games.
+    fn normalization(&self) -> Normalization {
+        Normalization::Exact
+    }
+
+    /// Returns the total points of a no-trump deal, including the dix de der.
+    fn no_trump_total(&self) -> i32 {
+        4 * (0..8)
+            .map(|r| self.no_trump_score(cards::Rank::from_n(r)))
+            .sum::<i32>()
+            + self.last_trick_bonus()
+    }
+
+    /// Returns the total points of an all-trump deal, including the dix de der.
+    fn all_trump_total(&self) -> i32 {
+        4 * (0..8)
+            .map(|r| self.all_trump_score(cards::Rank::from_n(r)))
+            .sum::<i32>()
+            + self.last_trick_bonus()
+    }
+}
+
+/// The classic scoring rules.
+///
+/// No-trump and all-trump games use the adjusted card values,
+/// so every deal totals 162 points.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassicScoring;
+
+impl ScoringScheme for ClassicScoring {}
+
+/// Scoring where no-trump and all-trump games use the usual card values.
+///
+/// A no-trump deal then totals 130 points, and an all-trump deal 258.
+/// Points are normalized back to 162, so contract targets keep their meaning.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawTrumpModeScoring;
+
+impl ScoringScheme for RawTrumpModeScoring {
+    fn no_trump_score(&self, rank: cards::Rank) -> i32 {
+        usual_score(rank)
+    }
+
+    fn all_trump_score(&self, rank: cards::Rank) -> i32 {
+        trump_score(rank)
+    }
+
+    fn normalization(&self) -> Normalization {
+        Normalization::To162
+    }
+}
+
+/// How points from a deal with an unusual total are brought back to the usual scale.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum Normalization {
+    /// Keep points as they are.
+    #[default]
+    Exact,
+    /// Scale points so that the deal totals 162.
+    To162,
+}
+
+/// Normalizes `points`, from a deal totalling `deal_total` points.
+pub fn normalize(points: i32, deal_total: i32, normalization: Normalization) -> i32 {
+    match normalization {
+        Normalization::Exact => points,
+        Normalization::To162 => (2 * points * DEAL_TOTAL + deal_total) / (2 * deal_total),
+    }
+}
+
+/// How to round trick points to a multiple of ten.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum RoundingRule {
+    /// Keep the exact number of points.
+    #[default]
+    Exact,
+    /// Round to the nearest ten, `5` rounds up (`85 -> 90`).
+    NearestHalfUp,
+    /// Round to the nearest ten, `5` rounds down (`85 -> 80`).
+    NearestHalfDown,
+    /// Always round up (`81 -> 90`).
+    Up,
+    /// Always round down (`89 -> 80`).
+    Down,
+}
+
+/// Rounds `points` to a multiple of ten according to `rule`.
+pub fn round_score(points: i32, rule: RoundingRule) -> i32 {
+    let base = points.div_euclid(10) * 10;
+    let rest = points.rem_euclid(10);
+    match rule {
+        RoundingRule::Exact => points,
+        RoundingRule::Down => base,
+        RoundingRule::Up if rest == 0 => base,
+        RoundingRule::Up => base + 10,
+        RoundingRule::NearestHalfUp if rest >= 5 => base + 10,
+        RoundingRule::NearestHalfUp => base,
+        RoundingRule::NearestHalfDown if rest > 5 => base + 10,
+        RoundingRule::NearestHalfDown => base,
+    }
+}
+
+/// Returns the number of points `card` is worth, with the current trump mode.
+pub fn score(card: cards::Card, trump: cards::Trump) -> i32 {
     let r = card.rank();
-    if card.suit() == trump {
-        trump_score(r)
-    } else {
-        usual_score(r)
+    match trump {
+        cards::Trump::Suit(suit) if suit == card.suit() => trump_score(r),
+        cards::Trump::Suit(_) => usual_score(r),
+        cards::Trump::NoTrump => no_trump_score(r),
+        cards::Trump::AllTrump => all_trump_score(r),
     }
 }
 
-/// Returns the strength of `card`, with the current trump suit.
-pub fn strength(card: cards::Card, trump: cards::Suit) -> i32 {
+/// Returns the number of points in `hand`, with the current trump mode.
+pub fn hand_score(hand: cards::Hand, trump: cards::Trump) -> i32 {
+    hand.list().into_iter().map(|c| score(c, trump)).sum()
+}
+
+/// Returns the number of points in the trumps of `hand`.
+pub fn hand_trump_score(hand: cards::Hand, trump: cards::Trump) -> i32 {
+    hand.list()
+        .into_iter()
+        .filter(|c| trump.is_trump(c.suit()))
+        .map(|c| score(c, trump))
+        .sum()
+}
+
+/// Returns the number of points in `hand`, not counting trumps.
+pub fn hand_plain_score(hand: cards::Hand, trump: cards::Trump) -> i32 {
+    hand.list()
+        .into_iter()
+        .filter(|c| !trump.is_trump(c.suit()))
+        .map(|c| score(c, trump))
+        .sum()
+}
+
+/// Returns the strength of `card`, with the current trump mode.
+pub fn strength(card: cards::Card, trump: cards::Trump) -> i32 {
     let r = card.rank();
-    if card.suit() == trump {
+    if trump.is_trump(card.suit()) {
         8 + trump_strength(r)
     } else {
         usual_strength(r)
     }
 }
 
-/// Returns the score for the given rank when it is the trump.
+// Lookup tables, indexed by `Rank::index()`:
+// 7, 8, 9, J, Q, K, X, A
+const TRUMP_SCORES: [i32; 8] = [0, 0, 14, 20, 3, 4, 10, 11];
+const USUAL_SCORES: [i32; 8] = [0, 0, 0, 2, 3, 4, 10, 11];
+const TRUMP_STRENGTHS: [i32; 8] = [0, 1, 6, 7, 2, 3, 4, 5];
+const USUAL_STRENGTHS: [i32; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+const NO_TRUMP_SCORES: [i32; 8] = [0, 0, 0, 2, 3, 4, 10, 19];
+const ALL_TRUMP_SCORES: [i32; 8] = [0, 0, 9, 14, 1, 3, 5, 6];
+
+/// Returns `true` if `card` beats `winner`, the card currently winning a trick.
 ///
-/// # Panics
-/// If `rank` is invalid.
-pub fn trump_score(rank: cards::Rank) -> i32 {
-    match rank {
-        cards::Rank::RankJ => 20,
-        cards::Rank::Rank9 => 14,
-        _ => usual_score(rank),
+/// `winner` is assumed to be either of the starting suit or a trump, so
+/// `card` beats it only if it is a stronger card of the same suit,
+/// or a trump when `winner` isn't.
+pub fn beats(card: cards::Card, winner: cards::Card, trump: cards::Trump) -> bool {
+    if card.suit() == winner.suit() {
+        strength(card, trump) > strength(winner, trump)
+    } else {
+        trump.is_trump(card.suit()) && !trump.is_trump(winner.suit())
     }
 }
 
-/// Returns the score for the given rank when it is not the trump.
+/// Returns the index of the winning card in a complete trick.
 ///
-/// # Panics
-/// If `rank` is invalid.
-pub fn usual_score(rank: cards::Rank) -> i32 {
-    match rank {
-        cards::Rank::Rank7 | cards::Rank::Rank8 | cards::Rank::Rank9 => 0,
-        cards::Rank::RankJ => 2,
-        cards::Rank::RankQ => 3,
-        cards::Rank::RankK => 4,
-        cards::Rank::RankX => 10,
-        cards::Rank::RankA => 11,
+/// `cards` is in play order, `lead` is the starting suit.
+pub fn trick_winner(cards: [cards::Card; 4], lead: cards::Suit, trump: cards::Trump) -> usize {
+    trick_winner_partial(&cards, lead, trump).expect("no card follows the starting suit")
+}
+
+/// Returns the index of the card currently winning a partial trick.
+///
+/// `cards` is in play order, `lead` is the starting suit.
+///
+/// Returns `None` if no card is of the starting suit or trump (for instance if `cards` is empty).
+pub fn trick_winner_partial(
+    cards: &[cards::Card],
+    lead: cards::Suit,
+    trump: cards::Trump,
+) -> Option<usize> {
+    let mut winner: Option<usize> = None;
+
+    for (i, &card) in cards.iter().enumerate() {
+        if card.suit() != lead && !trump.is_trump(card.suit()) {
+            continue;
+        }
+        winner = match winner {
+            Some(w) if !beats(card, cards[w], trump) => Some(w),
+            _ => Some(i),
+        };
     }
+
+    winner
+}
+
+/// Returns the score for the given rank when it is the trump.
+pub fn trump_score(rank: cards::Rank) -> i32 {
+    TRUMP_SCORES[rank.index()]
+}
+
+/// Returns the score for the given rank when it is not the trump.
+pub fn usual_score(rank: cards::Rank) -> i32 {
+    USUAL_SCORES[rank.index()]
 }
 
 /// Returns the strength for the given rank when it is the trump.
-///
-/// # Panics
-/// If `rank` is invalid.
 pub fn trump_strength(rank: cards::Rank) -> i32 {
-    match rank {
-        cards::Rank::Rank7 => 0,
-        cards::Rank::Rank8 => 1,
-        cards::Rank::RankQ => 2,
-        cards::Rank::RankK => 3,
-        cards::Rank::RankX => 4,
-        cards::Rank::RankA => 5,
-        cards::Rank::Rank9 => 6,
-        cards::Rank::RankJ => 7,
-    }
+    TRUMP_STRENGTHS[rank.index()]
 }
 
 /// Returns the strength for the given rank when it is not the trump.
-///
-/// # Panics
-/// If `rank` is invalid.
 pub fn usual_strength(rank: cards::Rank) -> i32 {
-    match rank {
-        cards::Rank::Rank7 => 0,
-        cards::Rank::Rank8 => 1,
-        cards::Rank::Rank9 => 2,
-        cards::Rank::RankJ => 3,
-        cards::Rank::RankQ => 4,
-        cards::Rank::RankK => 5,
-        cards::Rank::RankX => 6,
-        cards::Rank::RankA => 7,
+    USUAL_STRENGTHS[rank.index()]
+}
+
+/// Returns the score for the given rank in a no-trump (Sans-Atout) game.
+///
+/// Cards are ordered as usual (see `usual_strength`), but the ace is worth 19,
+/// so the deal still totals 152 points.
+pub fn no_trump_score(rank: cards::Rank) -> i32 {
+    NO_TRUMP_SCORES[rank.index()]
+}
+
+/// Returns the score for the given rank in an all-trump (Tout-Atout) game.
+///
+/// Every suit is ordered as trump (see `trump_strength`), but the jack is
+/// only worth 14, so the deal still totals 152 points.
+pub fn all_trump_score(rank: cards::Rank) -> i32 {
+    ALL_TRUMP_SCORES[rank.index()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards;
+
+    #[test]
+    fn test_total_score() {
+        let mut total = 0;
+        for s in 0..4 {
+            let suit = cards::Suit::from_n(s);
+            for r in 0..8 {
+                let card = cards::Card::new(suit, cards::Rank::from_n(r));
+                total += score(card, cards::Suit::Heart.into());
+            }
+        }
+        assert_eq!(total, CARDS_TOTAL);
+    }
+
+    #[test]
+    fn test_hand_score() {
+        let mut hand = cards::Hand::new();
+        hand.add(cards::Card::new(cards::Suit::Heart, cards::Rank::RankJ));
+        hand.add(cards::Card::new(cards::Suit::Heart, cards::Rank::Rank7));
+        hand.add(cards::Card::new(cards::Suit::Spade, cards::Rank::RankJ));
+        hand.add(cards::Card::new(cards::Suit::Club, cards::Rank::RankA));
+
+        let heart = cards::Trump::Suit(cards::Suit::Heart);
+        let diamond = cards::Trump::Suit(cards::Suit::Diamond);
+        assert_eq!(hand_score(hand, heart), 20 + 2 + 11);
+        assert_eq!(hand_trump_score(hand, heart), 20);
+        assert_eq!(hand_plain_score(hand, heart), 2 + 11);
+        assert_eq!(hand_score(hand, diamond), 2 + 2 + 11);
+        assert_eq!(hand_score(cards::Hand::new(), diamond), 0);
+        assert_eq!(hand_score(hand, cards::Trump::NoTrump), 2 + 2 + 19);
+        assert_eq!(hand_score(hand, cards::Trump::AllTrump), 14 + 14 + 6);
+        assert_eq!(hand_plain_score(hand, cards::Trump::AllTrump), 0);
+    }
+
+    #[test]
+    fn test_trick_winner() {
+        use cards::{Card, Rank, Suit, Trump};
+
+        let trump = Trump::Suit(Suit::Heart);
+
+        // Highest card of the starting suit wins, even against a stronger discard.
+        let trick = [
+            Card::new(Suit::Spade, Rank::Rank8),
+            Card::new(Suit::Club, Rank::RankA),
+            Card::new(Suit::Spade, Rank::RankK),
+            Card::new(Suit::Spade, Rank::Rank7),
+        ];
+        assert_eq!(trick_winner(trick, Suit::Spade, trump), 2);
+
+        // Any trump wins against the starting suit.
+        let trick = [
+            Card::new(Suit::Spade, Rank::RankA),
+            Card::new(Suit::Heart, Rank::Rank7),
+            Card::new(Suit::Heart, Rank::Rank9),
+            Card::new(Suit::Heart, Rank::RankA),
+        ];
+        assert_eq!(trick_winner(trick, Suit::Spade, trump), 2);
+
+        assert_eq!(
+            trick_winner_partial(&trick[..2], Suit::Spade, trump),
+            Some(1)
+        );
+        assert_eq!(trick_winner_partial(&[], Suit::Spade, trump), None);
+
+        // Without trump, only the starting suit can win.
+        assert_eq!(trick_winner(trick, Suit::Spade, Trump::NoTrump), 0);
+        // With all trumps, the starting suit is ordered as trump.
+        let trick = [
+            Card::new(Suit::Spade, Rank::RankA),
+            Card::new(Suit::Spade, Rank::Rank9),
+            Card::new(Suit::Heart, Rank::RankJ),
+            Card::new(Suit::Spade, Rank::RankX),
+        ];
+        assert_eq!(trick_winner(trick, Suit::Spade, Trump::AllTrump), 1);
+    }
+
+    #[test]
+    fn test_scoring_scheme() {
+        struct NoDixDeDer;
+        impl ScoringScheme for NoDixDeDer {
+            fn last_trick_bonus(&self) -> i32 {
+                0
+            }
+        }
+
+        for id in 0..32 {
+            let card = cards::Card::from_id(id);
+            let trump = cards::Suit::Club.into();
+            assert_eq!(ClassicScoring.score(card, trump), score(card, trump));
+            assert_eq!(NoDixDeDer.score(card, trump), score(card, trump));
+        }
+        assert_eq!(ClassicScoring.last_trick_bonus(), 10);
+        assert_eq!(NoDixDeDer.last_trick_bonus(), 0);
+
+        let spade = cards::Suit::Spade.into();
+        assert_eq!(ClassicScoring.deal_total(spade), DEAL_TOTAL);
+        assert_eq!(NoDixDeDer.deal_total(spade), CARDS_TOTAL);
+    }
+
+    #[test]
+    fn test_rank_orders() {
+        let trump: Vec<i32> = cards::Rank::by_trump_strength()
+            .map(trump_strength)
+            .collect();
+        assert_eq!(trump, (0..8).collect::<Vec<i32>>());
+
+        let plain: Vec<i32> = cards::Rank::by_plain_strength()
+            .rev()
+            .map(usual_strength)
+            .collect();
+        assert_eq!(plain, (0..8).rev().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_round_score() {
+        assert_eq!(round_score(84, RoundingRule::Exact), 84);
+        assert_eq!(round_score(84, RoundingRule::NearestHalfUp), 80);
+        assert_eq!(round_score(85, RoundingRule::NearestHalfUp), 90);
+        assert_eq!(round_score(85, RoundingRule::NearestHalfDown), 80);
+        assert_eq!(round_score(86, RoundingRule::NearestHalfDown), 90);
+        assert_eq!(round_score(81, RoundingRule::Up), 90);
+        assert_eq!(round_score(80, RoundingRule::Up), 80);
+        assert_eq!(round_score(89, RoundingRule::Down), 80);
+        assert_eq!(round_score(162, RoundingRule::NearestHalfUp), 160);
+        assert_eq!(round_score(0, RoundingRule::Up), 0);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let raw = RawTrumpModeScoring;
+        assert_eq!(raw.no_trump_total(), 130);
+        assert_eq!(raw.all_trump_total(), 258);
+        assert_eq!(ClassicScoring.no_trump_total(), DEAL_TOTAL);
+        assert_eq!(ClassicScoring.all_trump_total(), DEAL_TOTAL);
+
+        assert_eq!(normalize(65, 130, Normalization::To162), 81);
+        assert_eq!(normalize(130, 130, Normalization::To162), 162);
+        assert_eq!(normalize(258, 258, Normalization::To162), 162);
+        assert_eq!(normalize(0, 258, Normalization::To162), 0);
+        assert_eq!(normalize(65, 130, Normalization::Exact), 65);
+    }
+
+    #[test]
+    fn test_trump_modes_total() {
+        let ranks = (0..8).map(cards::Rank::from_n);
+        let no_trump: i32 = ranks.clone().map(no_trump_score).sum();
+        let all_trump: i32 = ranks.map(all_trump_score).sum();
+        assert_eq!(4 * no_trump, CARDS_TOTAL);
+        assert_eq!(4 * all_trump, CARDS_TOTAL);
+    }
+
+    #[test]
+    fn test_strengths() {
+        let suit = cards::Suit::Spade;
+        let trump = cards::Trump::Suit(suit);
+        let jack = cards::Card::new(suit, cards::Rank::RankJ);
+        let nine = cards::Card::new(suit, cards::Rank::Rank9);
+        let ace = cards::Card::new(suit, cards::Rank::RankA);
+        let plain_ace = cards::Card::new(cards::Suit::Heart, cards::Rank::RankA);
+        let seven = cards::Card::new(suit, cards::Rank::Rank7);
+
+        assert!(strength(jack, trump) > strength(nine, trump));
+        assert!(strength(nine, trump) > strength(ace, trump));
+        assert!(strength(seven, trump) > strength(plain_ace, trump));
+    }
+}
+
+#[cfg(all(test, feature = "use_bench"))]
+mod benchs {
+    use test::Bencher;
+
+    use super::*;
+    use crate::cards;
+
+    #[bench]
+    fn bench_score(b: &mut Bencher) {
+        let cards: Vec<_> = (0..32).map(cards::Card::from_id).collect();
+        b.iter(|| {
+            let mut total = 0;
+            for card in cards.iter() {
+                total += score(*card, cards::Suit::Heart.into());
+                total += strength(*card, cards::Suit::Heart.into());
+            }
+            test::black_box(total)
+        });
     }
 }
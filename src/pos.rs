@@ -1,5 +1,40 @@
 //! Player position in the table
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+/// Serde helpers to (de)serialize a `PlayerPos` or a `Team` as its numeric index.
+///
+/// By default, both types use their variant name (`"P2"`, `"T13"`).
+/// Use this module with `#[serde(with = "libcoinche::pos::as_index")]`
+/// to get the numeric representation (`2`, `1`) instead.
+pub mod as_index {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::convert::TryFrom;
+
+    /// Serializes `value` as its index.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<usize>,
+        S: Serializer,
+    {
+        serializer.serialize_u64((*value).into() as u64)
+    }
+
+    /// Deserializes a value from its index.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<usize, Error = String>,
+        D: Deserializer<'de>,
+    {
+        let n = usize::deserialize(deserializer)?;
+        T::try_from(n).map_err(D::Error::custom)
+    }
+}
+
 /// One of two teams
 #[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
 pub enum Team {
@@ -11,6 +46,9 @@ pub enum Team {
 
 impl Team {
     /// Return the team corresponding to the given number.
+    ///
+    /// For historical reasons, `2` and `3` are also accepted here.
+    /// Use `Team::try_from` for strict conversion.
     pub fn from_n(n: usize) -> Self {
         match n {
             // I shouldn't accept 2 or 3, but...
@@ -27,10 +65,57 @@ impl Team {
             Team::T13 => Team::T02,
         }
     }
+
+    /// Returns both teams.
+    pub fn all() -> [Team; 2] {
+        [Team::T02, Team::T13]
+    }
+
+    /// Returns the two players in this team.
+    pub fn players(self) -> [PlayerPos; 2] {
+        match self {
+            Team::T02 => [PlayerPos::P0, PlayerPos::P2],
+            Team::T13 => [PlayerPos::P1, PlayerPos::P3],
+        }
+    }
+
+    /// Returns `true` if `pos` belongs to this team.
+    pub fn has(self, pos: PlayerPos) -> bool {
+        pos.team() == self
+    }
+}
+
+impl From<Team> for usize {
+    fn from(team: Team) -> usize {
+        team as usize
+    }
+}
+
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Team::T02 => "T02",
+            Team::T13 => "T13",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl TryFrom<usize> for Team {
+    type Error = String;
+
+    /// Returns the team corresponding to the given number (`0` or `1`).
+    fn try_from(n: usize) -> Result<Self, String> {
+        match n {
+            0 => Ok(Team::T02),
+            1 => Ok(Team::T13),
+            other => Err(format!("invalid team number: {}", other)),
+        }
+    }
 }
 
 /// A position in the table
-#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
 pub enum PlayerPos {
     /// Player 0
     P0,
@@ -42,6 +127,98 @@ pub enum PlayerPos {
     P3,
 }
 
+/// Compass-style name for a seat at the table.
+///
+/// Play goes clockwise: North, East, South, West.
+/// North and South play together against East and West.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Seat {
+    /// Seat of player P0
+    North,
+    /// Seat of player P1
+    East,
+    /// Seat of player P2
+    South,
+    /// Seat of player P3
+    West,
+}
+
+impl Seat {
+    /// Returns the player position sitting at this seat.
+    pub fn pos(self) -> PlayerPos {
+        match self {
+            Seat::North => PlayerPos::P0,
+            Seat::East => PlayerPos::P1,
+            Seat::South => PlayerPos::P2,
+            Seat::West => PlayerPos::P3,
+        }
+    }
+}
+
+impl From<PlayerPos> for Seat {
+    fn from(pos: PlayerPos) -> Self {
+        pos.seat()
+    }
+}
+
+impl From<Seat> for PlayerPos {
+    fn from(seat: Seat) -> Self {
+        seat.pos()
+    }
+}
+
+impl fmt::Display for Seat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Seat::North => "North",
+            Seat::East => "East",
+            Seat::South => "South",
+            Seat::West => "West",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Seat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "N" | "n" | "north" | "North" => Ok(Seat::North),
+            "E" | "e" | "east" | "East" => Ok(Seat::East),
+            "S" | "s" | "south" | "South" => Ok(Seat::South),
+            "W" | "w" | "west" | "West" => Ok(Seat::West),
+            _ => Err(format!("invalid seat: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for PlayerPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            PlayerPos::P0 => "P0",
+            PlayerPos::P1 => "P1",
+            PlayerPos::P2 => "P2",
+            PlayerPos::P3 => "P3",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for PlayerPos {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "P0" | "p0" | "0" => Ok(PlayerPos::P0),
+            "P1" | "p1" | "1" => Ok(PlayerPos::P1),
+            "P2" | "p2" | "2" => Ok(PlayerPos::P2),
+            "P3" | "p3" | "3" => Ok(PlayerPos::P3),
+            _ => Err(format!("invalid pos: {}", s)),
+        }
+    }
+}
+
 /// Iterates on players
 pub struct PlayerIterator {
     current: PlayerPos,
@@ -63,7 +240,58 @@ impl Iterator for PlayerIterator {
     }
 }
 
+impl From<PlayerPos> for usize {
+    fn from(pos: PlayerPos) -> usize {
+        pos as usize
+    }
+}
+
+impl TryFrom<usize> for PlayerPos {
+    type Error = String;
+
+    /// Returns the position corresponding to the number (0 => P0, ...).
+    fn try_from(n: usize) -> Result<Self, String> {
+        match n {
+            0 => Ok(PlayerPos::P0),
+            1 => Ok(PlayerPos::P1),
+            2 => Ok(PlayerPos::P2),
+            3 => Ok(PlayerPos::P3),
+            other => Err(format!("invalid pos: {}", other)),
+        }
+    }
+}
+
 impl PlayerPos {
+    /// Returns an iterator over the four positions, starting with `P0`.
+    pub fn all() -> PlayerIterator {
+        PlayerPos::P0.until_n(4)
+    }
+
+    /// Returns the compass-style seat of this player.
+    pub fn seat(self) -> Seat {
+        match self {
+            PlayerPos::P0 => Seat::North,
+            PlayerPos::P1 => Seat::East,
+            PlayerPos::P2 => Seat::South,
+            PlayerPos::P3 => Seat::West,
+        }
+    }
+
+    /// Returns the player seated on the left (the next one to play).
+    pub fn left(self) -> PlayerPos {
+        self.next()
+    }
+
+    /// Returns the player seated on the right (the previous one to play).
+    pub fn right(self) -> PlayerPos {
+        self.prev()
+    }
+
+    /// Returns the player seated across the table (the partner).
+    pub fn across(self) -> PlayerPos {
+        self.partner()
+    }
+
     /// Returns the player's team
     pub fn team(self) -> Team {
         match self {
@@ -90,6 +318,16 @@ impl PlayerPos {
         self.team() == other.team()
     }
 
+    /// Returns the partner of this player (`P0 <-> P2`, `P1 <-> P3`).
+    pub fn partner(self) -> PlayerPos {
+        match self {
+            PlayerPos::P0 => PlayerPos::P2,
+            PlayerPos::P1 => PlayerPos::P3,
+            PlayerPos::P2 => PlayerPos::P0,
+            PlayerPos::P3 => PlayerPos::P1,
+        }
+    }
+
     /// Returns the next player in line
     pub fn next(self) -> PlayerPos {
         match self {
@@ -128,17 +366,45 @@ impl PlayerPos {
     }
 
     /// Returns the number of turns after `self` to reach `other`.
+    ///
+    /// Returns `4` if `self == other`.
+    #[deprecated(note = "use `offset_to` (0 if equal) or `turns_until` (4 if equal) instead")]
     pub fn distance_until(self, other: PlayerPos) -> usize {
+        self.turns_until(other)
+    }
+
+    /// Returns the number of seats from `self` to `other`, between 0 and 3.
+    ///
+    /// Returns `0` if `self == other`.
+    pub fn offset_to(self, other: PlayerPos) -> usize {
+        (4 + other as usize - self as usize) % 4
+    }
+
+    /// Returns the number of players to act from `self` (included) until `other` (excluded).
+    ///
+    /// This is between 1 and 4: if `self == other`, a full round is needed.
+    pub fn turns_until(self, other: PlayerPos) -> usize {
         (3 + other as usize - self as usize) % 4 + 1
     }
 
     /// Returns an iterator until the given player (`self` included, `other` excluded)
+    ///
+    /// If `self == other`, iterates on all four players.
     pub fn until(self, other: PlayerPos) -> PlayerIterator {
-        let d = self.distance_until(other);
+        let d = self.turns_until(other);
         self.until_n(d)
     }
 }
 
+impl Add<usize> for PlayerPos {
+    type Output = PlayerPos;
+
+    /// Returns the player `n` seats further.
+    fn add(self, n: usize) -> PlayerPos {
+        self.next_n(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -153,6 +419,22 @@ mod tests {
         assert_eq!(PlayerPos::P1.team(), Team::T13);
 
         assert!(PlayerPos::P0.team() != PlayerPos::P1.team());
+
+        for team in Team::all().iter() {
+            for pos in team.players().iter() {
+                assert_eq!(pos.team(), *team);
+                assert!(team.has(*pos));
+                assert!(!team.opponent().has(*pos));
+            }
+        }
+        assert_eq!(Team::T13.to_string(), "T13");
+
+        for i in 0..4 {
+            let pos = PlayerPos::from_n(i);
+            assert_eq!(pos.partner(), pos.next().next());
+            assert!(pos.is_partner(pos.partner()));
+            assert_eq!(pos.partner().partner(), pos);
+        }
     }
 
     #[test]
@@ -171,10 +453,88 @@ mod tests {
             assert!(*c == 5);
         }
 
+        let all: Vec<PlayerPos> = PlayerPos::all().collect();
+        assert_eq!(
+            all,
+            vec![PlayerPos::P0, PlayerPos::P1, PlayerPos::P2, PlayerPos::P3]
+        );
+
         for i in 0..4 {
             assert!(PlayerPos::from_n(i).next() == PlayerPos::from_n((i + 1) % 4));
             assert!(PlayerPos::from_n(i) == PlayerPos::from_n((i + 1) % 4).prev());
             assert!(PlayerPos::from_n(i).next().prev() == PlayerPos::from_n(i));
         }
     }
+
+    #[test]
+    fn test_offsets() {
+        for a in PlayerPos::all() {
+            assert_eq!(a.offset_to(a), 0);
+            assert_eq!(a.turns_until(a), 4);
+            assert_eq!(a + 4, a);
+            for b in PlayerPos::all() {
+                let offset = a.offset_to(b);
+                assert!(offset < 4);
+                assert_eq!(a + offset, b);
+                assert_eq!(a.until(b).count(), a.turns_until(b));
+            }
+        }
+
+        assert_eq!(PlayerPos::P3.offset_to(PlayerPos::P1), 2);
+        assert_eq!(PlayerPos::P3 + 2, PlayerPos::P1);
+    }
+
+    #[test]
+    fn test_seats() {
+        for pos in PlayerPos::all() {
+            assert_eq!(pos.seat().pos(), pos);
+            assert_eq!(pos.seat().to_string().parse::<Seat>(), Ok(pos.seat()));
+            assert_eq!(pos.left().right(), pos);
+            assert_eq!(pos.across(), pos.partner());
+        }
+
+        assert_eq!(PlayerPos::P0.seat(), Seat::North);
+        assert_eq!(PlayerPos::P0.left().seat(), Seat::East);
+        assert_eq!(PlayerPos::P0.right().seat(), Seat::West);
+        assert_eq!(PlayerPos::P0.across().seat(), Seat::South);
+        assert_eq!("w".parse::<Seat>(), Ok(Seat::West));
+        assert!("x".parse::<Seat>().is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Indexed {
+            #[serde(with = "as_index")]
+            pos: PlayerPos,
+            #[serde(with = "as_index")]
+            team: Team,
+        }
+
+        assert_eq!(serde_json::to_string(&PlayerPos::P2).unwrap(), "\"P2\"");
+        assert_eq!(serde_json::to_string(&Team::T13).unwrap(), "\"T13\"");
+
+        let indexed = Indexed {
+            pos: PlayerPos::P2,
+            team: Team::T13,
+        };
+        let json = serde_json::to_string(&indexed).unwrap();
+        assert_eq!(json, r#"{"pos":2,"team":1}"#);
+        assert_eq!(serde_json::from_str::<Indexed>(&json).unwrap(), indexed);
+        assert!(serde_json::from_str::<Indexed>(r#"{"pos":4,"team":1}"#).is_err());
+        assert!(serde_json::from_str::<Indexed>(r#"{"pos":0,"team":2}"#).is_err());
+    }
+
+    #[test]
+    fn test_try_from() {
+        for i in 0..4 {
+            assert_eq!(PlayerPos::try_from(i), Ok(PlayerPos::from_n(i)));
+        }
+        assert!(PlayerPos::try_from(4).is_err());
+
+        assert_eq!(Team::try_from(0), Ok(Team::T02));
+        assert_eq!(Team::try_from(1), Ok(Team::T13));
+        assert!(Team::try_from(2).is_err());
+        assert!(Team::try_from(3).is_err());
+    }
 }
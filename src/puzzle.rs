@@ -0,0 +1,209 @@
+//! Card play puzzles, with solution checking.
+
+use super::cards;
+use super::game;
+use super::pos;
+use super::solver;
+
+/// What the solver must achieve in a puzzle.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Goal {
+    /// Win this many of the remaining tricks.
+    Tricks(usize),
+    /// Win the deal: fulfil the contract, or make it fail when defending.
+    WinDeal,
+}
+
+/// A position to solve, with both hands of a team revealed.
+///
+/// The solver plays for both players of `team`, and the opponents defend as well as possible.
+#[derive(Clone)]
+pub struct Puzzle {
+    /// Position to start from.
+    pub position: game::GameState,
+    /// Team played by the solver.
+    pub team: pos::Team,
+    /// What the solver must achieve.
+    pub goal: Goal,
+}
+
+impl Puzzle {
+    /// Creates a new puzzle.
+    pub fn new(position: game::GameState, team: pos::Team, goal: Goal) -> Self {
+        Puzzle {
+            position,
+            team,
+            goal,
+        }
+    }
+
+    /// Checks a proposed solution: the cards played by `team`, in order.
+    ///
+    /// The opponents defend with the solver's best cards, and after each card of the solution,
+    /// the goal must still be reachable against any defense. This is only practical near the
+    /// end of the deal.
+    ///
+    /// Returns an error describing where the solution fails.
+    pub fn check(&self, solution: &[cards::Card]) -> Result<(), String> {
+        let mut solver = solver::Solver::with_objective(match self.goal {
+            Goal::Tricks(_) => solver::Objective::Tricks,
+            Goal::WinDeal => solver::Objective::Points,
+        });
+        let mut game = self.position.clone().with_events(false);
+        let mut rest = solution;
+        let mut line = Vec::new();
+
+        while !self.reached(&game) {
+            if game.is_over() {
+                return Err(describe(&line, "the goal is not reached"));
+            }
+
+            let player = game.next_player();
+            let ours = player.team() == self.team;
+            let card = if ours {
+                match rest.split_first() {
+                    None => {
+                        return Err(describe(
+                            &line,
+                            "the solution ends before the goal is reached",
+                        ))
+                    }
+                    Some((&card, tail)) => {
+                        rest = tail;
+                        card
+                    }
+                }
+            } else {
+                // Values are for the defenders' team here.
+                solver
+                    .evaluate(&game)
+                    .into_iter()
+                    .max_by_key(|&(_, value)| value)
+                    .unwrap()
+                    .0
+            };
+
+            if let Err(err) = game.play_inner(player, card, None) {
+                let reason = format!("{} cannot play {}: {}", player, card, err);
+                return Err(describe(&line, &reason));
+            }
+            line.push((player, card));
+
+            if ours && !self.reachable(&mut solver, &game) {
+                return Err(describe(&line, "the defense can now prevent the goal"));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Returns `true` if the goal is reached in `game`.
+    fn reached(&self, game: &game::GameState) -> bool {
+        match self.goal {
+            Goal::Tricks(n) => {
+                let start = self.position.tricks().len();
+                let won = game.tricks()[start..]
+                    .iter()
+                    .filter(|trick| trick.winner.team() == self.team)
+                    .count();
+                won >= n
+            }
+            Goal::WinDeal => {
+                game.is_over()
+                    && match game.projected_result(game::Projection::Stop) {
+                        game::GameResult::GameOver { winners, .. } => winners == self.team,
+                        game::GameResult::Nothing => false,
+                    }
+            }
+        }
+    }
+
+    // Returns `true` if the goal is reached when both teams play perfectly from `game`.
+    fn reachable(&self, solver: &mut solver::Solver, game: &game::GameState) -> bool {
+        let mut end = game.clone();
+        for (player, card) in solver.solve(game).line {
+            if self.reached(&end) {
+                return true;
+            }
+            end.play_inner(player, card, None).unwrap();
+        }
+        self.reached(&end)
+    }
+}
+
+// Describes why a solution fails after the given cards.
+fn describe(line: &[(pos::PlayerPos, cards::Card)], reason: &str) -> String {
+    if line.is_empty() {
+        return reason.to_string();
+    }
+    let played: Vec<String> = line
+        .iter()
+        .map(|(p, card)| format!("{} by {}", card, p))
+        .collect();
+    format!("after {}: {}", played.join(", "), reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, game, pos};
+
+    #[test]
+    fn test_puzzle() {
+        use cards::{Card, Rank, Suit};
+
+        // Each player holds a full suit, but P0 and P1 swapped their sevens.
+        let mut hands = crate::suit_hands();
+        crate::swap_cards(
+            &mut hands,
+            (pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::Rank7)),
+            (pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank7)),
+        );
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80♥").unwrap();
+        let mut position = game::GameState::new(pos::PlayerPos::P0, hands, contract);
+
+        // Draw the trump first, and keep the seven of spades for the end.
+        let hearts = [
+            Rank::RankJ,
+            Rank::Rank9,
+            Rank::RankA,
+            Rank::RankX,
+            Rank::RankK,
+            Rank::RankQ,
+            Rank::Rank8,
+        ];
+        let mut solution = Vec::new();
+        for (&heart, club) in hearts.iter().zip(cards::Rank::by_plain_strength()) {
+            solution.push(Card::new(Suit::Heart, heart));
+            solution.push(Card::new(Suit::Club, club));
+        }
+
+        // Start with four tricks left: each card is followed by a defender.
+        for &card in &solution[..8] {
+            let player = position.next_player();
+            position.play_card(player, card).unwrap();
+            let defender = player.next();
+            let card = position.legal_moves(defender).unwrap().get_card();
+            position.play_card(defender, card).unwrap();
+        }
+        let mut solution = solution.split_off(8);
+
+        let puzzle = Puzzle::new(position.clone(), pos::Team::T02, Goal::Tricks(3));
+        assert_eq!(puzzle.check(&solution), Ok(()));
+        assert!(puzzle.check(&solution[..4]).is_err());
+
+        // Leading the seven of spades gives the lead away.
+        let mut bad = solution.clone();
+        bad.insert(0, Card::new(Suit::Spade, Rank::Rank7));
+        assert!(puzzle.check(&bad).is_err());
+
+        // The last trick is always lost.
+        solution.push(Card::new(Suit::Spade, Rank::Rank7));
+        solution.push(Card::new(Suit::Club, Rank::RankA));
+        let puzzle = Puzzle::new(position.clone(), pos::Team::T02, Goal::Tricks(4));
+        assert!(puzzle.check(&solution).is_err());
+
+        let puzzle = Puzzle::new(position, pos::Team::T02, Goal::WinDeal);
+        assert_eq!(puzzle.check(&solution), Ok(()));
+    }
+}
@@ -0,0 +1,194 @@
+//! Text rendering of a game in progress, for debugging and terminal clients.
+//!
+//! Only available with the `cli` feature.
+
+use std::fmt::Write;
+
+use super::cards;
+use super::game;
+use super::pos;
+
+// Width of each of the three columns of the table.
+const WIDTH: usize = 16;
+
+// ANSI escape sequences.
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const UNDERLINE: &str = "\x1b[4m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders the table as seen by `viewer`, seated at the bottom.
+///
+/// Shows the contract, the cards on the table, the tricks won by each player,
+/// and the hand of `viewer` only. The next player to play is marked with a `*`.
+///
+/// ```text
+/// Contract: P0: 100♥ X    Trick 2/8
+///                      P0 (0)
+///                        --
+///       *P3 (0) --                9♠ P1 (1)
+///                        7♠
+///                   P2 (you) (0)
+/// Points: 0 - 24
+/// Hand: A♥ Q♠ X♦ 7♣ X♣ A♣
+/// ```
+pub fn render(game: &game::GameState, viewer: pos::PlayerPos) -> String {
+    let hand: Vec<String> = game.hands()[viewer as usize]
+        .list()
+        .iter()
+        .map(cards::Card::to_string)
+        .collect();
+    render_table(game, viewer, &hand.join(" "))
+}
+
+/// Renders the table like `render`, with the hand of `viewer` colored for terminals.
+///
+/// See `colored_hand`.
+pub fn render_colored(game: &game::GameState, viewer: pos::PlayerPos) -> String {
+    let hand = game.hands()[viewer as usize];
+    let legal = game.legal_moves(viewer).unwrap_or_default();
+    render_table(
+        game,
+        viewer,
+        &colored_hand(hand, game.contract().trump, legal),
+    )
+}
+
+/// Renders a card with ANSI colors: hearts and diamonds in red, and trumps in bold.
+pub fn colored_card(card: cards::Card, trump: cards::Trump) -> String {
+    let mut style = String::new();
+    if card.suit() == cards::Suit::Heart || card.suit() == cards::Suit::Diamond {
+        style.push_str(RED);
+    }
+    if trump.is_trump(card.suit()) {
+        style.push_str(BOLD);
+    }
+    if style.is_empty() {
+        card.to_string()
+    } else {
+        format!("{}{}{}", style, card, RESET)
+    }
+}
+
+/// Renders a hand with ANSI colors, like `colored_card`, and underlines the cards in `legal`.
+pub fn colored_hand(hand: cards::Hand, trump: cards::Trump, legal: cards::Hand) -> String {
+    let cards: Vec<String> = hand
+        .list()
+        .into_iter()
+        .map(|card| {
+            let colored = colored_card(card, trump);
+            if legal.has(card) {
+                format!("{}{}{}", UNDERLINE, colored, RESET)
+            } else {
+                colored
+            }
+        })
+        .collect();
+    cards.join(" ")
+}
+
+fn render_table(game: &game::GameState, viewer: pos::PlayerPos, hand: &str) -> String {
+    let trick = game.current_trick();
+    let card =
+        |p: pos::PlayerPos| trick.cards[p as usize].map_or("--".to_string(), |c| c.to_string());
+    let label = |p: pos::PlayerPos| {
+        let won = game.tricks().iter().filter(|t| t.winner == p).count();
+        let you = if p == viewer { " (you)" } else { "" };
+        let turn = if !game.is_over() && game.next_player() == p {
+            "*"
+        } else {
+            ""
+        };
+        format!("{}{}{} ({})", turn, p, you, won)
+    };
+
+    let top = viewer.across();
+    let left = viewer.left();
+    let right = viewer.right();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Contract: {:#}    Trick {}/8",
+        game.contract(),
+        game.trick_number()
+    )
+    .unwrap();
+    writeln!(out, "{:w$}{:^w$}", "", label(top), w = WIDTH).unwrap();
+    writeln!(out, "{:w$}{:^w$}", "", card(top), w = WIDTH).unwrap();
+    writeln!(
+        out,
+        "{:>w$}{:w$}{:<w$}",
+        format!("{} {}", label(left), card(left)),
+        "",
+        format!("{} {}", card(right), label(right)),
+        w = WIDTH
+    )
+    .unwrap();
+    writeln!(out, "{:w$}{:^w$}", "", card(viewer), w = WIDTH).unwrap();
+    writeln!(out, "{:w$}{:^w$}", "", label(viewer), w = WIDTH).unwrap();
+    let points = game.points();
+    writeln!(out, "Points: {} - {}", points[0], points[1]).unwrap();
+    writeln!(out, "Hand: {}", hand).unwrap();
+
+    out.lines()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, game, pos};
+
+    #[test]
+    fn test_render() {
+        let hands = crate::deal_seeded_hands([2; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "100♥ X").unwrap();
+        let mut game = game::GameState::new(pos::PlayerPos::P0, hands, contract);
+        for _ in 0..6 {
+            let player = game.next_player();
+            let card = game.legal_moves(player).unwrap().get_card();
+            game.play_card(player, card).unwrap();
+        }
+
+        let table = render(&game, pos::PlayerPos::P2);
+        assert!(table.starts_with("Contract: P0: 100♥ X    Trick 2/8\n"));
+        assert!(table.contains("P2 (you)"));
+        // Only the viewer's hand is shown.
+        let hand = game.hands()[2].list();
+        assert!(table.ends_with(&format!("{}\n", hand.last().unwrap())));
+        assert_eq!(table.lines().count(), 8);
+
+        // The colored table only differs by its last line.
+        let colored = render_colored(&game, pos::PlayerPos::P2);
+        let plain: Vec<&str> = table.lines().collect();
+        let lines: Vec<&str> = colored.lines().collect();
+        assert_eq!(plain[..7], lines[..7]);
+        assert!(lines[7].contains(RESET));
+    }
+
+    #[test]
+    fn test_colored() {
+        use cards::{Card, Rank, Suit, Trump};
+
+        let trump = Trump::Suit(Suit::Heart);
+        let heart = Card::new(Suit::Heart, Rank::RankJ);
+        let diamond = Card::new(Suit::Diamond, Rank::Rank7);
+        let spade = Card::new(Suit::Spade, Rank::RankA);
+        assert_eq!(colored_card(heart, trump), "\x1b[31m\x1b[1mJ♥\x1b[0m");
+        assert_eq!(colored_card(diamond, trump), "\x1b[31m7♦\x1b[0m");
+        assert_eq!(colored_card(spade, trump), "A♠");
+        assert_eq!(colored_card(spade, Trump::AllTrump), "\x1b[1mA♠\x1b[0m");
+
+        let mut hand = cards::Hand::new();
+        hand.add(spade);
+        hand.add(diamond);
+        let mut legal = cards::Hand::new();
+        legal.add(spade);
+        let colored = colored_hand(hand, Trump::NoTrump, legal);
+        assert!(colored.contains("\x1b[4mA♠\x1b[0m"));
+        assert!(colored.contains("\x1b[31m7♦\x1b[0m"));
+        assert!(!colored.contains("\x1b[4m\x1b[31m"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Table rules selecting among the common scoring conventions.
+
+/// How a team scores when its contract is fulfilled.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum SuccessScoring {
+    /// The team only scores the contract value.
+    #[default]
+    ContractOnly,
+    /// The team scores the contract value plus the points it made.
+    ///
+    /// The defense also scores the points it made.
+    /// Points are rounded with the `ScoringScheme` rounding rule.
+    ContractPlusPoints,
+}
+
+/// How the defense scores when the contract fails ("dedans").
+///
+/// In every case, the score is multiplied if the contract was coinched.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum FailureScoring {
+    /// The defense scores a flat 160.
+    #[default]
+    Flat,
+    /// The defense scores 160 plus the contract value.
+    FlatPlusContract,
+}
+
+/// Set of rules used to play and score a game.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct RuleSet {
+    /// Scoring when the contract is fulfilled.
+    pub success: SuccessScoring,
+    /// Scoring when the contract fails.
+    pub failure: FailureScoring,
+    /// `true` if declarations (sequences and carrés) are played.
+    pub announces: bool,
+    /// `true` if a player without the led suit must trump even when their partner
+    /// is winning the trick ("pisser sur son partenaire" is not allowed).
+    #[serde(default)]
+    pub must_trump_on_partner: bool,
+    /// `true` if a player need not raise over their partner's winning card
+    /// ("monter sur son partenaire" is not required).
+    #[serde(default)]
+    pub no_overtrump_on_partner: bool,
+    /// Points for winning the last trick (dix de der).
+    ///
+    /// `None` uses the `last_trick_bonus` of the scoring scheme.
+    #[serde(default)]
+    pub dix_de_der: Option<i32>,
+    /// `true` if a team winning every trick does not get the dix de der on top of the capot.
+    #[serde(default)]
+    pub no_dix_de_der_on_capot: bool,
+    /// `true` if an illegal card is accepted and recorded as a renonce, instead of rejected.
+    ///
+    /// The first team to renounce loses the deal and scores nothing.
+    /// This lets results from a physical table be entered as they were played.
+    #[serde(default)]
+    pub record_renounces: bool,
+}
+
+/// Score given to the defense on a failed contract, before adding the contract value.
+pub const FAILURE_SCORE: i32 = 160;
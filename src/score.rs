@@ -0,0 +1,187 @@
+//! Keep track of scores across multiple deals (the "marque").
+
+use super::bid;
+use super::game;
+use super::points;
+use super::pos;
+
+/// Details of a single deal, as written on the score sheet.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct DealRecord {
+    /// Contract played during this deal.
+    pub contract: bid::Contract,
+    /// Trick points won by each team (including the dix de der).
+    pub points: [i32; 2],
+    /// Belote points for each team.
+    pub belote: [i32; 2],
+    /// Declaration (announces) points for each team.
+    pub declarations: [i32; 2],
+    /// Multiplier applied to the contract (`1`, `2` if coinched, `4` if surcoinched).
+    pub multiplier: i32,
+    /// Team that won this deal.
+    pub winners: pos::Team,
+    /// Final score of each team, after rounding.
+    pub scores: [i32; 2],
+}
+
+impl DealRecord {
+    /// Builds a record from a finished game.
+    ///
+    /// Returns `None` if the game is not over yet.
+    pub fn from_result(
+        contract: &bid::Contract,
+        result: &game::GameResult,
+        rounding: points::RoundingRule,
+    ) -> Option<Self> {
+        match *result {
+            game::GameResult::Nothing => None,
+            game::GameResult::GameOver {
+                points,
+                winners,
+                scores,
+                belote,
+                declarations,
+                ..
+            } => Some(DealRecord {
+                contract: contract.clone(),
+                points,
+                belote: match belote {
+                    Some(pos::Team::T02) => [points::BELOTE_SCORE, 0],
+                    Some(pos::Team::T13) => [0, points::BELOTE_SCORE],
+                    None => [0; 2],
+                },
+                declarations,
+                multiplier: contract.multiplier(),
+                winners,
+                scores: [
+                    points::round_score(scores[0], rounding),
+                    points::round_score(scores[1], rounding),
+                ],
+            }),
+        }
+    }
+}
+
+/// Accumulates the scores of both teams across deals.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct ScoreSheet {
+    rounding: points::RoundingRule,
+    deals: Vec<DealRecord>,
+}
+
+impl ScoreSheet {
+    /// Creates an empty score sheet, using the given rounding rule.
+    pub fn new(rounding: points::RoundingRule) -> Self {
+        ScoreSheet {
+            rounding,
+            deals: Vec::new(),
+        }
+    }
+
+    /// Returns the rounding rule used by this score sheet.
+    pub fn rounding(&self) -> points::RoundingRule {
+        self.rounding
+    }
+
+    /// Adds a deal record to the sheet.
+    pub fn push(&mut self, record: DealRecord) {
+        self.deals.push(record);
+    }
+
+    /// Records a finished game.
+    ///
+    /// Returns the new record, or `None` if the game is not over yet.
+    pub fn record(
+        &mut self,
+        contract: &bid::Contract,
+        result: &game::GameResult,
+    ) -> Option<&DealRecord> {
+        let record = DealRecord::from_result(contract, result, self.rounding)?;
+        self.deals.push(record);
+        self.deals.last()
+    }
+
+    /// Returns all the deals recorded so far.
+    pub fn deals(&self) -> &[DealRecord] {
+        &self.deals
+    }
+
+    /// Returns the total score of each team.
+    pub fn totals(&self) -> [i32; 2] {
+        let mut totals = [0; 2];
+        for deal in &self.deals {
+            totals[0] += deal.scores[0];
+            totals[1] += deal.scores[1];
+        }
+        totals
+    }
+
+    /// Returns the total score of the given team.
+    pub fn total(&self, team: pos::Team) -> i32 {
+        self.totals()[team as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, game, points, pos};
+
+    #[test]
+    fn test_score_sheet() {
+        let contract = bid::Contract {
+            author: pos::PlayerPos::P1,
+            trump: cards::Trump::Suit(cards::Suit::Spade),
+            target: bid::Target::Contract100,
+            coinche_level: 0,
+            raises: Vec::new(),
+        };
+
+        let mut sheet = ScoreSheet::new(points::RoundingRule::NearestHalfUp);
+        assert!(sheet
+            .record(&contract, &game::GameResult::Nothing)
+            .is_none());
+
+        let result = game::GameResult::GameOver {
+            points: [45, 117],
+            winners: pos::Team::T13,
+            scores: [0, 120],
+            belote: Some(pos::Team::T13),
+            declarations: [0; 2],
+            contract: contract.clone(),
+            fulfilled: true,
+            margin: Some(37),
+            last_trick: pos::Team::T13,
+            dix_de_der: Some(pos::Team::T13),
+            capot: None,
+            multiplier: 1,
+            conceded: None,
+            renounce: None,
+        };
+        sheet.record(&contract, &result).unwrap();
+
+        let result = game::GameResult::GameOver {
+            points: [95, 67],
+            winners: pos::Team::T02,
+            scores: [160, 0],
+            belote: None,
+            declarations: [0; 2],
+            contract: contract.clone(),
+            fulfilled: false,
+            margin: Some(-33),
+            last_trick: pos::Team::T02,
+            dix_de_der: Some(pos::Team::T02),
+            capot: None,
+            multiplier: 1,
+            conceded: None,
+            renounce: None,
+        };
+        sheet.record(&contract, &result).unwrap();
+
+        assert_eq!(sheet.deals().len(), 2);
+        assert_eq!(sheet.deals()[0].multiplier, 1);
+        assert_eq!(sheet.deals()[0].belote, [0, 20]);
+        assert_eq!(sheet.totals(), [160, 120]);
+        assert_eq!(sheet.total(pos::Team::T13), 120);
+    }
+}
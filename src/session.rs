@@ -0,0 +1,309 @@
+//! Play consecutive deals until a team reaches the target score (a "partie").
+
+use std::fmt;
+
+use super::bid;
+use super::cards;
+use super::game;
+use super::points;
+use super::pos;
+use super::rules;
+use super::score;
+
+/// Usual target score for a match.
+pub const DEFAULT_TARGET: i32 = 1000;
+
+/// Error that can occur when recording a deal in a match.
+#[derive(Eq, PartialEq, Debug)]
+pub enum MatchError {
+    /// A team already won the match.
+    MatchOver,
+    /// The auction or game is not the current deal of the match (ex: it was already recorded).
+    WrongDeal,
+    /// The game is not over yet.
+    DealRunning,
+    /// Too many deals in a row were cancelled because every player passed.
+    Stalled,
+    /// The auction could not complete.
+    Bid(bid::BidError),
+}
+
+impl From<bid::BidError> for MatchError {
+    fn from(err: bid::BidError) -> Self {
+        MatchError::Bid(err)
+    }
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MatchError::MatchOver => write!(f, "the match is over"),
+            MatchError::WrongDeal => write!(f, "this is not the current deal of the match"),
+            MatchError::DealRunning => write!(f, "the deal is not over yet"),
+            MatchError::Stalled => write!(f, "too many deals in a row were passed out"),
+            MatchError::Bid(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// A match: consecutive deals, until a team reaches the target score.
+///
+/// The match rotates the dealer, and keeps the score sheet. Each deal goes through
+/// `new_auction`, then `end_auction` and, unless every player passed, `end_deal`.
+/// Each auction and game is checked against the current deal, so none is recorded twice.
+#[derive(Clone, Debug)]
+pub struct Match {
+    target: i32,
+    dealer: pos::PlayerPos,
+    auction_rules: bid::AuctionRules,
+    rules: rules::RuleSet,
+    sheet: score::ScoreSheet,
+    cancelled: usize,
+    // Contract and hands of the game being played, if the auction is over.
+    playing: Option<(bid::Contract, [cards::Hand; 4])>,
+}
+
+impl Match {
+    /// Starts a match, with `dealer` dealing the first deal.
+    ///
+    /// The match ends when a team reaches `target` points (usually 1000, 1001 or 2000).
+    pub fn new(dealer: pos::PlayerPos, target: i32) -> Self {
+        Match {
+            target,
+            dealer,
+            auction_rules: bid::AuctionRules::default(),
+            rules: rules::RuleSet::default(),
+            sheet: score::ScoreSheet::new(points::RoundingRule::default()),
+            cancelled: 0,
+            playing: None,
+        }
+    }
+
+    /// Sets the rules used by every auction.
+    pub fn with_auction_rules(mut self, rules: bid::AuctionRules) -> Self {
+        self.auction_rules = rules;
+        self
+    }
+
+    /// Sets the rules used to play and score every deal.
+    pub fn with_rules(mut self, rules: rules::RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Sets the rounding rule used on the score sheet.
+    pub fn with_rounding(mut self, rounding: points::RoundingRule) -> Self {
+        self.sheet = score::ScoreSheet::new(rounding);
+        self
+    }
+
+    /// Returns the target score.
+    pub fn target(&self) -> i32 {
+        self.target
+    }
+
+    /// Returns the player dealing the current deal.
+    pub fn dealer(&self) -> pos::PlayerPos {
+        self.dealer
+    }
+
+    /// Returns the number of deals cancelled because every player passed.
+    pub fn cancelled(&self) -> usize {
+        self.cancelled
+    }
+
+    /// Returns the score sheet, with every deal played so far.
+    pub fn sheet(&self) -> &score::ScoreSheet {
+        &self.sheet
+    }
+
+    /// Returns the total score of each team.
+    pub fn scores(&self) -> [i32; 2] {
+        self.sheet.totals()
+    }
+
+    /// Starts the auction for the current deal, with freshly dealt cards.
+    ///
+    /// The player after the dealer speaks first.
+    pub fn new_auction(&self) -> bid::Auction {
+        bid::Auction::new(self.dealer.next())
+            .with_dealer(self.dealer)
+            .with_rules(self.auction_rules)
+    }
+
+    /// Ends the auction of the current deal.
+    ///
+    /// If every player passed, the deal is cancelled and returns `Ok(None)`:
+    /// the next dealer deals again.
+    /// Otherwise, returns the game to play, or gives back the auction if it is not complete,
+    /// or not dealt by the current dealer while no game is being played.
+    pub fn end_auction(
+        &mut self,
+        auction: bid::Auction,
+    ) -> Result<Option<game::GameState>, (Box<bid::Auction>, MatchError)> {
+        if self.is_over() {
+            return Err((Box::new(auction), MatchError::MatchOver));
+        }
+        if self.playing.is_some() || auction.dealer() != self.dealer {
+            return Err((Box::new(auction), MatchError::WrongDeal));
+        }
+        if auction.get_state() == bid::AuctionState::Cancelled {
+            self.cancelled += 1;
+            self.dealer = self.dealer.next();
+            return Ok(None);
+        }
+        let game = auction
+            .complete()
+            .map_err(|(auction, err)| (auction, err.into()))?;
+        self.playing = Some((game.contract().clone(), dealt_hands(&game)));
+        Ok(Some(game.with_rules(self.rules)))
+    }
+
+    /// Records a finished deal, and passes the deal to the next player.
+    ///
+    /// `game` must be the one returned by the last `end_auction`.
+    /// Returns the new record.
+    pub fn end_deal(&mut self, game: &game::GameState) -> Result<&score::DealRecord, MatchError> {
+        if self.is_over() {
+            return Err(MatchError::MatchOver);
+        }
+        match self.playing {
+            Some((ref contract, hands))
+                if contract == game.contract() && hands == dealt_hands(game) => {}
+            _ => return Err(MatchError::WrongDeal),
+        }
+        if !game.is_over() {
+            return Err(MatchError::DealRunning);
+        }
+
+        let result = game.projected_result(game::Projection::Stop);
+        self.playing = None;
+        self.dealer = self.dealer.next();
+        self.sheet
+            .record(game.contract(), &result)
+            .ok_or(MatchError::DealRunning)
+    }
+
+    /// Returns the team that won the match, if any.
+    ///
+    /// When both teams reach the target on the same deal, the highest score wins.
+    /// On a tie, the match goes on.
+    pub fn winner(&self) -> Option<pos::Team> {
+        let [a, b] = self.scores();
+        if a.max(b) < self.target || a == b {
+            None
+        } else if a > b {
+            Some(pos::Team::T02)
+        } else {
+            Some(pos::Team::T13)
+        }
+    }
+
+    /// Returns `true` if a team won the match.
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+}
+
+// Returns the cards dealt to each player: those left in hand, and those played.
+fn dealt_hands(game: &game::GameState) -> [cards::Hand; 4] {
+    let mut hands = game.hands();
+    for trick in game.tricks().iter().chain(Some(game.current_trick())) {
+        for (p, card) in trick.iter_in_play_order() {
+            hands[p as usize].add(card);
+        }
+    }
+    hands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, pos};
+
+    #[test]
+    fn test_match() {
+        let mut partie = Match::new(pos::PlayerPos::P3, 300);
+
+        // Everybody passes: the next player deals again.
+        let mut auction = partie.new_auction();
+        assert_eq!(auction.first(), pos::PlayerPos::P0);
+        assert_eq!(auction.dealer(), pos::PlayerPos::P3);
+        for p in pos::PlayerPos::P0.until_n(4) {
+            auction.pass(p).unwrap();
+        }
+        assert!(partie.end_auction(auction).unwrap().is_none());
+        assert_eq!(partie.dealer(), pos::PlayerPos::P0);
+        assert_eq!(partie.cancelled(), 1);
+
+        while !partie.is_over() {
+            let dealer = partie.dealer();
+            let mut auction = partie.new_auction();
+            let first = auction.first();
+            auction
+                .bid(first, cards::Suit::Heart.into(), bid::Target::Contract80)
+                .unwrap();
+            for p in first.next().until_n(3) {
+                auction.pass(p).unwrap();
+            }
+            let mut game = partie.end_auction(auction).unwrap().unwrap();
+            assert_eq!(
+                partie.end_deal(&game).map(|_| ()),
+                Err(MatchError::DealRunning)
+            );
+            // No other deal starts while this one is played.
+            let other = partie.new_auction();
+            assert_eq!(
+                partie
+                    .end_auction(other)
+                    .map(|_| ())
+                    .map_err(|(_, err)| err),
+                Err(MatchError::WrongDeal)
+            );
+            while !game.is_over() {
+                let p = game.next_player();
+                let card = game.legal_moves(p).unwrap().get_card();
+                game.play_card(p, card).unwrap();
+            }
+            assert!(partie.end_deal(&game).is_ok());
+            assert_eq!(partie.dealer(), dealer.next());
+
+            // The same deal is not recorded twice.
+            let deals = partie.sheet().deals().len();
+            if !partie.is_over() {
+                assert_eq!(
+                    partie.end_deal(&game).map(|_| ()),
+                    Err(MatchError::WrongDeal)
+                );
+            }
+            assert_eq!(partie.sheet().deals().len(), deals);
+            assert_eq!(partie.dealer(), dealer.next());
+        }
+
+        let scores = partie.scores();
+        assert!(scores[0].max(scores[1]) >= 300);
+        let winner = partie.winner().unwrap();
+        assert!(scores[winner as usize] > scores[1 - winner as usize]);
+
+        // No deal can be started once the match is over.
+        let auction = partie.new_auction();
+        assert!(partie.end_auction(auction).is_err());
+
+        // A stale auction, from a previous dealer, is rejected.
+        let mut partie = Match::new(pos::PlayerPos::P3, 300);
+        let stale = partie.new_auction();
+        let mut auction = partie.new_auction();
+        for p in pos::PlayerPos::P0.until_n(4) {
+            auction.pass(p).unwrap();
+        }
+        assert!(partie.end_auction(auction).unwrap().is_none());
+        assert_eq!(
+            partie
+                .end_auction(stale)
+                .map(|_| ())
+                .map_err(|(_, err)| err),
+            Err(MatchError::WrongDeal)
+        );
+        assert_eq!(partie.cancelled(), 1);
+    }
+}
@@ -0,0 +1,354 @@
+//! Perfect-information ("double-dummy") solver.
+//!
+//! With every hand visible, finds the points each team makes when both play perfectly.
+
+use std::collections::HashMap;
+
+use super::cards;
+use super::game;
+use super::points;
+use super::pos;
+
+/// Outcome of the deal when both teams play perfectly.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Solution {
+    /// Trick points made by each team at the end of the deal, including the dix de der.
+    pub points: [i32; 2],
+    /// Tricks won by each team at the end of the deal.
+    pub tricks: [usize; 2],
+    /// A perfect line of play, from the current position to the end of the deal.
+    pub line: Vec<(pos::PlayerPos, cards::Card)>,
+}
+
+/// What the solver maximizes for each team.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum Objective {
+    /// Trick points, including the dix de der.
+    #[default]
+    Points,
+    /// Number of tricks won.
+    Tricks,
+}
+
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+// Position: hash of the game, cards on the table and remaining hands.
+//
+// The game hash only covers the cards played, so the hands tell deals apart.
+type Key = (u64, [Option<u32>; 4], [cards::Hand; 4]);
+
+/// Solves positions with alpha-beta search.
+///
+/// Searched positions are remembered, so solving the following positions of the same deal is fast.
+/// A solver can be reused across deals.
+#[derive(Default)]
+pub struct Solver {
+    memo: HashMap<Key, (i32, Bound)>,
+    objective: Objective,
+}
+
+impl Solver {
+    /// Creates a new solver, maximizing trick points.
+    pub fn new() -> Self {
+        Solver::default()
+    }
+
+    /// Creates a new solver with the given objective.
+    pub fn with_objective(objective: Objective) -> Self {
+        Solver {
+            memo: HashMap::new(),
+            objective,
+        }
+    }
+
+    /// Solves the game from its current position.
+    ///
+    /// This is only practical for a few tricks in debug builds, but a whole deal
+    /// can be solved in release builds.
+    pub fn solve(&mut self, game: &game::GameState) -> Solution {
+        let mut game = game.clone().with_events(false);
+        let mut line = Vec::new();
+        while !game.is_over() {
+            let player = game.next_player();
+            let (card, _) = self.best_move(&mut game);
+            game.play_inner(player, card, None).unwrap();
+            line.push((player, card));
+        }
+
+        let mut tricks = [0; 2];
+        for trick in game.tricks() {
+            tricks[trick.winner.team() as usize] += 1;
+        }
+        Solution {
+            points: game.points(),
+            tricks,
+            line,
+        }
+    }
+
+    /// Returns the final trick points of the next player's team for each of their legal cards,
+    /// when both teams play perfectly afterwards.
+    ///
+    /// With `Objective::Tricks`, this is the number of tricks won at the end of the deal instead.
+    ///
+    /// Returns an empty list if the game is over.
+    pub fn evaluate(&mut self, game: &game::GameState) -> Vec<(cards::Card, i32)> {
+        if game.is_over() {
+            return Vec::new();
+        }
+        let mut game = game.clone().with_events(false);
+        let player = game.next_player();
+        let team = player.team() as usize;
+        let current = self.totals(&game);
+        let remaining = match self.objective {
+            Objective::Points => game.remaining_points(),
+            Objective::Tricks => game.tricks_remaining() as i32,
+        };
+
+        moves(&game)
+            .into_iter()
+            .map(|card| {
+                let value = self.value_after(&mut game, player, card);
+                let own = if team == 0 {
+                    current[0] + value
+                } else {
+                    current[1] + remaining - value
+                };
+                (card, own)
+            })
+            .collect()
+    }
+
+    // Returns what each team made so far, in the unit of the objective.
+    fn totals(&self, game: &game::GameState) -> [i32; 2] {
+        match self.objective {
+            Objective::Points => game.points(),
+            Objective::Tricks => {
+                let mut tricks = [0; 2];
+                for trick in game.tricks() {
+                    tricks[trick.winner.team() as usize] += 1;
+                }
+                tricks
+            }
+        }
+    }
+
+    // Returns the best card for the next player, and what team 0 makes from now on.
+    fn best_move(&mut self, game: &mut game::GameState) -> (cards::Card, i32) {
+        let player = game.next_player();
+        let maximizing = player.team() == pos::Team::T02;
+        let mut best = None;
+        for card in moves(game) {
+            let value = self.value_after(game, player, card);
+            let better = match best {
+                None => true,
+                Some((_, b)) if maximizing => value > b,
+                Some((_, b)) => value < b,
+            };
+            if better {
+                best = Some((card, value));
+            }
+        }
+        best.unwrap()
+    }
+
+    // Returns what team 0 makes from now on, when `player` plays `card`.
+    fn value_after(
+        &mut self,
+        game: &mut game::GameState,
+        player: pos::PlayerPos,
+        card: cards::Card,
+    ) -> i32 {
+        let before = self.totals(game)[0];
+        game.play_inner(player, card, None).unwrap();
+        let gain = self.totals(game)[0] - before;
+        let value = gain + self.search(game, i32::MIN / 2, i32::MAX / 2);
+        game.undo();
+        value
+    }
+
+    // Returns what team 0 makes from now on, within the `alpha..beta` window.
+    fn search(&mut self, game: &mut game::GameState, mut alpha: i32, mut beta: i32) -> i32 {
+        if game.is_over() {
+            return 0;
+        }
+
+        let trick = game.current_trick();
+        let key = (
+            game.hash(),
+            trick.cards.map(|card| card.map(cards::Card::id)),
+            game.hands(),
+        );
+        let window = (alpha, beta);
+        if let Some(&(value, bound)) = self.memo.get(&key) {
+            match bound {
+                Bound::Exact => return value,
+                Bound::Lower if value >= beta => return value,
+                Bound::Upper if value <= alpha => return value,
+                Bound::Lower => alpha = alpha.max(value),
+                Bound::Upper => beta = beta.min(value),
+            }
+        }
+
+        let player = game.next_player();
+        let maximizing = player.team() == pos::Team::T02;
+        let mut best = if maximizing { i32::MIN } else { i32::MAX };
+        for card in moves(game) {
+            let before = self.totals(game)[0];
+            game.play_inner(player, card, None).unwrap();
+            let gain = self.totals(game)[0] - before;
+            let value = gain + self.search(game, alpha - gain, beta - gain);
+            game.undo();
+
+            if maximizing {
+                best = best.max(value);
+                alpha = alpha.max(value);
+            } else {
+                best = best.min(value);
+                beta = beta.min(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= window.0 {
+            Bound::Upper
+        } else if best >= window.1 {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.memo.insert(key, (best, bound));
+        best
+    }
+}
+
+// Legal cards for the next player, strongest first to prune sooner.
+fn moves(game: &game::GameState) -> Vec<cards::Card> {
+    let trump = game.contract().trump;
+    let mut cards = game.legal_moves(game.next_player()).unwrap().list();
+    cards.sort_by_key(|&card| -points::strength(card, trump));
+    cards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, game, pos};
+    use rand::SeedableRng;
+
+    // Plain minimax, without pruning nor memoization.
+    fn minimax(game: &mut game::GameState) -> i32 {
+        if game.is_over() {
+            return game.points()[0];
+        }
+        let player = game.next_player();
+        let values = game
+            .legal_moves(player)
+            .unwrap()
+            .list()
+            .into_iter()
+            .map(|card| {
+                game.play_card(player, card).unwrap();
+                let value = minimax(game);
+                game.undo();
+                value
+            });
+        if player.team() == pos::Team::T02 {
+            values.max().unwrap()
+        } else {
+            values.min().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_solver() {
+        let mut rng = rand::rngs::StdRng::from_seed([3; 32]);
+        for seed in 0..4 {
+            let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♦").unwrap();
+            let hands = crate::deal_seeded_hands([seed; 32]);
+            let mut game =
+                game::GameState::new(pos::PlayerPos::P0, hands, contract).with_events(false);
+            while game.tricks_remaining() > 3 {
+                let player = game.next_player();
+                let card = game.legal_moves(player).unwrap().pick_random_n(&mut rng, 1);
+                game.play_card(player, card.get_card()).unwrap();
+            }
+
+            let mut solver = Solver::new();
+            let solution = solver.solve(&game);
+            assert_eq!(solution.points[0], minimax(&mut game.clone()));
+            assert_eq!(solution.points[0] + solution.points[1], 162);
+            assert_eq!(solution.tricks[0] + solution.tricks[1], 8);
+            let cards: usize = game.hands().iter().map(|hand| hand.size()).sum();
+            assert_eq!(solution.line.len(), cards);
+
+            // Following the line reaches the same points.
+            let mut replay = game.clone();
+            for &(player, card) in &solution.line {
+                replay.play_card(player, card).unwrap();
+            }
+            assert_eq!(replay.points(), solution.points);
+
+            // No card does better than the solution, and one reaches it.
+            let team = game.next_player().team() as usize;
+            let values = solver.evaluate(&game);
+            assert!(values.iter().all(|&(_, v)| v <= solution.points[team]));
+            assert!(values.iter().any(|&(_, v)| v == solution.points[team]));
+        }
+    }
+
+    #[test]
+    fn test_solver_reuse() {
+        let mut rng = rand::rngs::StdRng::from_seed([5; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P0, "80SA").unwrap();
+        let hands = crate::deal_seeded_hands([7; 32]);
+        let mut game =
+            game::GameState::new(pos::PlayerPos::P0, hands, contract.clone()).with_events(false);
+        let mut history = Vec::new();
+        while game.tricks_remaining() > 3 {
+            let player = game.next_player();
+            let card = game.legal_moves(player).unwrap().pick_random_n(&mut rng, 1);
+            game.play_card(player, card.get_card()).unwrap();
+            history.push((player, card.get_card()));
+        }
+
+        // Swap two unplayed cards of the same suit between opponents:
+        // without trump, the same cards can be played, but the deal differs.
+        let remaining = game.hands();
+        let (a, b) = remaining[0]
+            .list()
+            .into_iter()
+            .find_map(|a| {
+                remaining[1]
+                    .list()
+                    .into_iter()
+                    .find(|b| b.suit() == a.suit())
+                    .map(|b| (a, b))
+            })
+            .unwrap();
+        let mut other_hands = hands;
+        crate::swap_cards(
+            &mut other_hands,
+            (pos::PlayerPos::P0, a),
+            (pos::PlayerPos::P1, b),
+        );
+        let mut other =
+            game::GameState::new(pos::PlayerPos::P0, other_hands, contract).with_events(false);
+        for &(player, card) in &history {
+            other.play_card(player, card).unwrap();
+        }
+        assert_eq!(game.hash(), other.hash());
+
+        let mut solver = Solver::new();
+        solver.solve(&game);
+        assert_eq!(solver.solve(&other), Solver::new().solve(&other));
+        assert_eq!(solver.evaluate(&other), Solver::new().evaluate(&other));
+    }
+}
@@ -0,0 +1,569 @@
+//! Keep track of the cards seen during the card play, and what they reveal about each hand.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::cards;
+use super::game;
+use super::pos;
+use super::rules;
+use super::trick;
+
+/// Cards seen so far, and suits each player is known to lack.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Tracker {
+    trump: cards::Trump,
+    rules: rules::RuleSet,
+    played: [cards::Hand; 4],
+    // `voids[p][suit]` is `true` if player `p` has no card left in `suit`.
+    voids: [[bool; 4]; 4],
+}
+
+impl Tracker {
+    /// Creates a new tracker, before any card is played.
+    pub fn new(trump: cards::Trump, rules: rules::RuleSet) -> Self {
+        Tracker {
+            trump,
+            rules,
+            played: [cards::Hand::new(); 4],
+            voids: [[false; 4]; 4],
+        }
+    }
+
+    /// Creates a tracker from the tricks played so far, including the current one.
+    pub fn from_tricks(
+        tricks: &[trick::Trick],
+        trump: cards::Trump,
+        rules: rules::RuleSet,
+    ) -> Self {
+        let mut tracker = Tracker::new(trump, rules);
+        for played in tricks {
+            // Replay the trick, to know the winner when each card was played.
+            let mut trick = trick::Trick::new(played.first);
+            for (p, card) in played.iter_in_play_order() {
+                tracker.record(p, card, &trick);
+                trick.play_card(p, card, trump);
+            }
+        }
+        tracker
+    }
+
+    /// Records `card` played by `player` on `trick`.
+    ///
+    /// `trick` is the state of the trick before the card was played.
+    pub fn record(&mut self, player: pos::PlayerPos, card: cards::Card, trick: &trick::Trick) {
+        let mut unseen = self.unseen();
+        unseen.remove(card);
+        self.played[player as usize].add(card);
+
+        // A suit is void if any of its cards would have made this one illegal.
+        let excluded = excluded(player, card, unseen, trick, self.trump, &self.rules);
+        for suit in (0..4).map(cards::Suit::from_n) {
+            let left = unseen.suit_cards(suit);
+            if !left.is_empty() && excluded.suit_cards(suit) == left {
+                self.set_void(player, suit);
+            }
+        }
+    }
+
+    fn set_void(&mut self, player: pos::PlayerPos, suit: cards::Suit) {
+        self.voids[player as usize][suit_index(suit)] = true;
+    }
+
+    /// Returns every card played so far.
+    pub fn seen(&self) -> cards::Hand {
+        self.played
+            .iter()
+            .fold(cards::Hand::new(), |seen, &hand| seen.union(hand))
+    }
+
+    /// Returns every card not played yet.
+    ///
+    /// A player can remove their own hand from it to get the cards held by the others.
+    pub fn unseen(&self) -> cards::Hand {
+        full_deck().difference(self.seen())
+    }
+
+    /// Returns the cards played so far by `player`.
+    pub fn played_by(&self, player: pos::PlayerPos) -> cards::Hand {
+        self.played[player as usize]
+    }
+
+    /// Returns `true` if `player` is known to have no card left in `suit`.
+    pub fn is_void(&self, player: pos::PlayerPos, suit: cards::Suit) -> bool {
+        self.voids[player as usize][suit_index(suit)]
+    }
+
+    /// Returns the suits `player` is known to have no card left in.
+    pub fn voids(&self, player: pos::PlayerPos) -> Vec<cards::Suit> {
+        (0..4)
+            .map(cards::Suit::from_n)
+            .filter(|&suit| self.is_void(player, suit))
+            .collect()
+    }
+}
+
+/// What a player can deduce about the hands of the others, from the cards played so far.
+///
+/// For every card not seen yet, it keeps the set of players who could still hold it.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Knowledge {
+    seat: pos::PlayerPos,
+    trump: cards::Trump,
+    rules: rules::RuleSet,
+    // Cards each player may still hold.
+    possible: [cards::Hand; 4],
+    // Number of cards each player still holds.
+    held: [usize; 4],
+}
+
+impl Knowledge {
+    /// Creates the knowledge of `seat`, holding `hand`, before any card is played.
+    pub fn new(
+        seat: pos::PlayerPos,
+        hand: cards::Hand,
+        trump: cards::Trump,
+        rules: rules::RuleSet,
+    ) -> Self {
+        let mut possible = [full_deck().difference(hand); 4];
+        possible[seat as usize] = hand;
+        Knowledge {
+            seat,
+            trump,
+            rules,
+            possible,
+            held: [8; 4],
+        }
+    }
+
+    /// Creates the knowledge of `seat` from the tricks played so far, including the current one.
+    ///
+    /// `hand` is the current hand of `seat`, without the cards it already played.
+    pub fn from_tricks(
+        seat: pos::PlayerPos,
+        hand: cards::Hand,
+        tricks: &[trick::Trick],
+        trump: cards::Trump,
+        rules: rules::RuleSet,
+    ) -> Self {
+        let mut dealt = hand;
+        for trick in tricks {
+            if let Some(card) = trick.cards[seat as usize] {
+                dealt.add(card);
+            }
+        }
+
+        let mut knowledge = Knowledge::new(seat, dealt, trump, rules);
+        for played in tricks {
+            let mut trick = trick::Trick::new(played.first);
+            for (p, card) in played.iter_in_play_order() {
+                knowledge.record(p, card, &trick);
+                trick.play_card(p, card, trump);
+            }
+        }
+        knowledge
+    }
+
+    /// Records `card` played by `player` on `trick`.
+    ///
+    /// `trick` is the state of the trick before the card was played.
+    pub fn record(&mut self, player: pos::PlayerPos, card: cards::Card, trick: &trick::Trick) {
+        // Any card that would have made this one illegal is not in the hand.
+        let candidates = self.possible[player as usize];
+        let excluded = excluded(player, card, candidates, trick, self.trump, &self.rules);
+
+        let possible = &mut self.possible[player as usize];
+        *possible = possible.difference(excluded);
+        for possible in self.possible.iter_mut() {
+            possible.remove(card);
+        }
+        self.held[player as usize] -= 1;
+        self.propagate();
+    }
+
+    // Deduces more from the number of cards each player holds, until nothing changes.
+    fn propagate(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for p in pos::PlayerPos::all() {
+                let i = p as usize;
+                let others = pos::PlayerPos::all()
+                    .filter(|&q| q != p)
+                    .fold(cards::Hand::new(), |others, q| {
+                        others.union(self.possible[q as usize])
+                    });
+
+                // A player with as many candidates as cards holds all of them.
+                if self.possible[i].size() == self.held[i]
+                    && !others.intersection(self.possible[i]).is_empty()
+                {
+                    for q in pos::PlayerPos::all().filter(|&q| q != p) {
+                        let q = q as usize;
+                        self.possible[q] = self.possible[q].difference(self.possible[i]);
+                    }
+                    changed = true;
+                }
+
+                // A player who is the only candidate for as many cards as they hold has nothing else.
+                let sure = self.possible[i].difference(others);
+                if sure.size() == self.held[i] && sure != self.possible[i] {
+                    self.possible[i] = sure;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// Returns the player whose point of view this is.
+    pub fn seat(&self) -> pos::PlayerPos {
+        self.seat
+    }
+
+    /// Returns the players who could still hold `card`.
+    ///
+    /// Returns an empty list if the card was already played.
+    pub fn holders(&self, card: cards::Card) -> Vec<pos::PlayerPos> {
+        pos::PlayerPos::all()
+            .filter(|&p| self.possible[p as usize].has(card))
+            .collect()
+    }
+
+    /// Returns the cards `player` could still hold.
+    pub fn possible_cards(&self, player: pos::PlayerPos) -> cards::Hand {
+        self.possible[player as usize]
+    }
+
+    /// Returns the number of cards `player` still holds.
+    pub fn held(&self, player: pos::PlayerPos) -> usize {
+        self.held[player as usize]
+    }
+
+    /// Deals the cards not played yet at random, following this knowledge.
+    ///
+    /// Every distribution consistent with what was seen (voids shown, cards played,
+    /// number of cards held) is equally likely. The hand of `seat` is always its own.
+    ///
+    /// Returns `None` if no distribution is consistent, which may only happen when
+    /// renounces were recorded.
+    ///
+    /// To draw several deals, build a `Sampler` once with `sampler` instead.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<[cards::Hand; 4]> {
+        self.sampler().sample(rng)
+    }
+
+    /// Returns a sampler drawing deals that follow this knowledge.
+    pub fn sampler(&self) -> Sampler {
+        Sampler::new(self)
+    }
+
+    /// Returns the cards `player` is known to hold.
+    pub fn known_cards(&self, player: pos::PlayerPos) -> cards::Hand {
+        pos::PlayerPos::all()
+            .filter(|&q| q != player)
+            .fold(self.possible[player as usize], |known, q| {
+                known.difference(self.possible[q as usize])
+            })
+    }
+}
+
+/// Draws deals consistent with a `Knowledge`.
+///
+/// The deals are counted card after card; the counts are kept from one sample to the next.
+pub struct Sampler {
+    // Cards not played yet, with their possible holders.
+    cards: Vec<(cards::Card, Vec<pos::PlayerPos>)>,
+    // Number of cards each player holds.
+    held: [usize; 4],
+    // Number of deals for the cards from an index, with room left in each hand.
+    memo: HashMap<(usize, [usize; 4]), u64>,
+}
+
+impl Sampler {
+    fn new(knowledge: &Knowledge) -> Self {
+        let cards = full_deck()
+            .list()
+            .into_iter()
+            .map(|card| (card, knowledge.holders(card)))
+            .filter(|(_, holders)| !holders.is_empty())
+            .collect();
+        Sampler {
+            cards,
+            held: knowledge.held,
+            memo: HashMap::new(),
+        }
+    }
+
+    /// Deals the cards not played yet at random.
+    ///
+    /// See `Knowledge::sample`.
+    pub fn sample<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<[cards::Hand; 4]> {
+        let mut room = self.held;
+        if self.count(0, room) == 0 {
+            return None;
+        }
+
+        let mut hands = [cards::Hand::new(); 4];
+        for i in 0..self.cards.len() {
+            // Pick a holder in proportion to the number of deals it leaves.
+            let (card, holders) = self.cards[i].clone();
+            let mut pick = rng.gen_range(0..self.count(i, room));
+            for p in holders {
+                let p = p as usize;
+                if room[p] == 0 {
+                    continue;
+                }
+                room[p] -= 1;
+                let ways = self.count(i + 1, room);
+                if pick < ways {
+                    hands[p].add(card);
+                    break;
+                }
+                pick -= ways;
+                room[p] += 1;
+            }
+        }
+        Some(hands)
+    }
+
+    fn count(&mut self, i: usize, room: [usize; 4]) -> u64 {
+        if i == self.cards.len() {
+            return if room == [0; 4] { 1 } else { 0 };
+        }
+        if let Some(&ways) = self.memo.get(&(i, room)) {
+            return ways;
+        }
+
+        let mut ways = 0;
+        for p in self.cards[i].1.clone() {
+            let p = p as usize;
+            if room[p] > 0 {
+                let mut next = room;
+                next[p] -= 1;
+                ways += self.count(i + 1, next);
+            }
+        }
+        self.memo.insert((i, room), ways);
+        ways
+    }
+}
+
+// Returns the cards among `candidates` that would have made `card` illegal on `trick`.
+fn excluded(
+    player: pos::PlayerPos,
+    card: cards::Card,
+    candidates: cards::Hand,
+    trick: &trick::Trick,
+    trump: cards::Trump,
+    rules: &rules::RuleSet,
+) -> cards::Hand {
+    let mut excluded = cards::Hand::new();
+    for other in candidates.list() {
+        let mut pair = cards::Hand::new();
+        pair.add(card);
+        pair.add(other);
+        if game::can_play_with(player, card, pair, trick, trump, rules).is_err() {
+            excluded.add(other);
+        }
+    }
+    excluded
+}
+
+fn full_deck() -> cards::Hand {
+    let mut all = cards::Hand::new();
+    for id in 0..32 {
+        all.add(cards::Card::from_id(id));
+    }
+    all
+}
+
+// Inverse of `Suit::from_n`.
+fn suit_index(suit: cards::Suit) -> usize {
+    (suit as u32).trailing_zeros() as usize / 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bid, cards, game, pos, rules, trick};
+
+    #[test]
+    fn test_tracker() {
+        use cards::{Card, Rank, Suit};
+
+        let trump = cards::Trump::Suit(Suit::Heart);
+        let mut tricks = vec![trick::Trick::new(pos::PlayerPos::P0)];
+        let plays = [
+            (pos::PlayerPos::P0, Card::new(Suit::Spade, Rank::RankA)),
+            // P1 discards while P0 is winning: no spade nor heart left.
+            (pos::PlayerPos::P1, Card::new(Suit::Club, Rank::Rank7)),
+            // P2 discards on its partner: only the spades are gone.
+            (pos::PlayerPos::P2, Card::new(Suit::Diamond, Rank::Rank8)),
+            // P3 trumps.
+            (pos::PlayerPos::P3, Card::new(Suit::Heart, Rank::Rank7)),
+        ];
+        for &(p, card) in plays.iter() {
+            tricks[0].play_card(p, card, trump);
+        }
+        tricks.push(trick::Trick::new(pos::PlayerPos::P3));
+        tricks[1].play_card(
+            pos::PlayerPos::P3,
+            Card::new(Suit::Club, Rank::RankA),
+            trump,
+        );
+
+        let tracker = Tracker::from_tricks(&tricks, trump, rules::RuleSet::default());
+        assert_eq!(tracker.seen().size(), 5);
+        assert_eq!(tracker.unseen().size(), 27);
+        assert!(!tracker.unseen().has(Card::new(Suit::Club, Rank::RankA)));
+        assert!(tracker
+            .played_by(pos::PlayerPos::P3)
+            .has(Card::new(Suit::Heart, Rank::Rank7)));
+
+        assert!(tracker.voids(pos::PlayerPos::P0).is_empty());
+        assert_eq!(
+            tracker.voids(pos::PlayerPos::P1),
+            vec![Suit::Heart, Suit::Spade]
+        );
+        assert_eq!(tracker.voids(pos::PlayerPos::P2), vec![Suit::Spade]);
+        assert_eq!(tracker.voids(pos::PlayerPos::P3), vec![Suit::Spade]);
+        assert!(!tracker.is_void(pos::PlayerPos::P3, Suit::Heart));
+
+        // When trumping on a partner is required, P2 has no heart either.
+        let rules = rules::RuleSet {
+            must_trump_on_partner: true,
+            ..rules::RuleSet::default()
+        };
+        let tracker = Tracker::from_tricks(&tricks, trump, rules);
+        assert_eq!(
+            tracker.voids(pos::PlayerPos::P2),
+            vec![Suit::Heart, Suit::Spade]
+        );
+    }
+
+    #[test]
+    fn test_knowledge() {
+        use cards::{Card, Rank, Suit};
+
+        let trump = cards::Trump::Suit(Suit::Heart);
+        let mut hand = cards::Hand::new();
+        hand.add(Card::new(Suit::Spade, Rank::RankA));
+        let mut knowledge =
+            Knowledge::new(pos::PlayerPos::P0, hand, trump, rules::RuleSet::default());
+        let ace = Card::new(Suit::Spade, Rank::RankA);
+        assert_eq!(knowledge.holders(ace), vec![pos::PlayerPos::P0]);
+        assert_eq!(
+            knowledge.holders(Card::new(Suit::Club, Rank::Rank7)).len(),
+            3
+        );
+
+        let mut trick = trick::Trick::new(pos::PlayerPos::P0);
+        let plays = [
+            (pos::PlayerPos::P0, ace),
+            // P1 trumps: no spade left.
+            (pos::PlayerPos::P1, Card::new(Suit::Heart, Rank::RankQ)),
+            // P2 plays a lower trump: no spade, and no trump higher than the queen.
+            (pos::PlayerPos::P2, Card::new(Suit::Heart, Rank::Rank7)),
+        ];
+        for &(p, card) in plays.iter() {
+            knowledge.record(p, card, &trick);
+            trick.play_card(p, card, trump);
+        }
+
+        assert!(knowledge.holders(ace).is_empty());
+        assert_eq!(
+            knowledge.holders(Card::new(Suit::Spade, Rank::Rank7)),
+            vec![pos::PlayerPos::P3]
+        );
+        assert_eq!(
+            knowledge.holders(Card::new(Suit::Heart, Rank::RankJ)),
+            vec![pos::PlayerPos::P1, pos::PlayerPos::P3]
+        );
+        assert_eq!(
+            knowledge.holders(Card::new(Suit::Heart, Rank::Rank8)),
+            vec![pos::PlayerPos::P1, pos::PlayerPos::P2, pos::PlayerPos::P3]
+        );
+        // The other spades must all be with P3.
+        assert_eq!(
+            knowledge
+                .known_cards(pos::PlayerPos::P3)
+                .suit_cards(Suit::Spade)
+                .size(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_sample() {
+        use rand::SeedableRng;
+
+        let hands = crate::deal_seeded_hands([5; 32]);
+        let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♦").unwrap();
+        let mut game = game::GameState::new(pos::PlayerPos::P0, hands, contract);
+        let mut rng = rand::rngs::StdRng::from_seed([5; 32]);
+        while game.tricks_remaining() > 2 {
+            let player = game.next_player();
+            let card = game.hint(player).unwrap();
+            game.play_card(player, card).unwrap();
+        }
+
+        let knowledge = game.knowledge(pos::PlayerPos::P0);
+        let mut sampler = knowledge.sampler();
+        let mut seen = std::collections::HashMap::new();
+        for _ in 0..10000 {
+            let sample = sampler.sample(&mut rng).unwrap();
+            assert_eq!(sample[0], game.hands()[0]);
+            for p in pos::PlayerPos::all() {
+                let hand = sample[p as usize];
+                assert_eq!(hand.size(), knowledge.held(p));
+                assert_eq!(
+                    hand.difference(knowledge.possible_cards(p)),
+                    cards::Hand::new()
+                );
+            }
+            let key = sample.map(|hand| {
+                hand.list()
+                    .into_iter()
+                    .map(cards::Card::id)
+                    .collect::<Vec<_>>()
+            });
+            *seen.entry(key).or_insert(0) += 1;
+        }
+
+        // Every consistent deal is about as likely.
+        assert!(seen.len() > 1);
+        let mean = 10000 / seen.len();
+        for &count in seen.values() {
+            assert!(count * 10 > mean * 7 && count * 10 < mean * 13);
+        }
+    }
+
+    #[test]
+    fn test_knowledge_is_sound() {
+        for seed in 0..8 {
+            let hands = crate::deal_seeded_hands([seed; 32]);
+            let contract = bid::Contract::parse(pos::PlayerPos::P1, "80♦").unwrap();
+            let mut game = game::GameState::new(pos::PlayerPos::P0, hands, contract);
+            while !game.is_over() {
+                for seat in pos::PlayerPos::all() {
+                    let knowledge = game.knowledge(seat);
+                    for p in pos::PlayerPos::all() {
+                        let hand = game.hands()[p as usize];
+                        let possible = knowledge.possible_cards(p);
+                        assert_eq!(hand.difference(possible), cards::Hand::new());
+                        assert_eq!(
+                            knowledge.known_cards(p).difference(hand),
+                            cards::Hand::new()
+                        );
+                    }
+                    assert_eq!(knowledge.possible_cards(seat), game.hands()[seat as usize]);
+                }
+
+                let player = game.next_player();
+                let card = game.hint(player).unwrap();
+                game.play_card(player, card).unwrap();
+            }
+        }
+    }
+}
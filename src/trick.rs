@@ -1,11 +1,20 @@
 //! This module implements a trick in a game of coinche.
 
+use std::convert::TryFrom;
+
 use super::cards;
 use super::points;
 use super::pos;
 
 /// The current cards on the table.
-#[derive(Clone, serde::Serialize, Debug)]
+///
+/// Deserialized tricks are checked for consistency: cards must be played in order
+/// from `first`, and `winner` must hold the best card.
+///
+/// Tricks saved without their cached strength do not say whether the led suit
+/// was trump; call `recompute_winner` on them once the trump is known.
+#[derive(Eq, PartialEq, Clone, Copy, serde::Serialize, serde::Deserialize, Debug)]
+#[serde(try_from = "TrickData")]
 pub struct Trick {
     /// Cards currently on the table (they are `None` until played).
     pub cards: [Option<cards::Card>; 4],
@@ -13,6 +22,10 @@ pub struct Trick {
     pub first: pos::PlayerPos,
     /// Current winner of the trick (updated after each card played).
     pub winner: pos::PlayerPos,
+    // Strength of the winning card.
+    strength: i32,
+    // Cards that would beat the winning card in its own suit.
+    beating: cards::Hand,
 }
 
 impl Trick {
@@ -22,14 +35,25 @@ impl Trick {
             first,
             winner: first,
             cards: [None; 4],
+            strength: 0,
+            beating: cards::Hand::new(),
         }
     }
 
     /// Returns the points value of this trick.
-    pub fn score(&self, trump: cards::Suit) -> i32 {
+    pub fn score(&self, trump: cards::Trump) -> i32 {
+        self.score_with(trump, &points::ClassicScoring)
+    }
+
+    /// Returns the points value of this trick, using the given scoring scheme.
+    pub fn score_with<S: points::ScoringScheme + ?Sized>(
+        &self,
+        trump: cards::Trump,
+        scheme: &S,
+    ) -> i32 {
         self.cards
             .iter()
-            .map(|c| c.map_or(0, |c| points::score(c, trump)))
+            .map(|c| c.map_or(0, |c| scheme.score(c, trump)))
             .sum()
     }
 
@@ -42,20 +66,114 @@ impl Trick {
         &mut self,
         player: pos::PlayerPos,
         card: cards::Card,
-        trump: cards::Suit,
+        trump: cards::Trump,
     ) -> bool {
         self.cards[player as usize] = Some(card);
-        if player == self.first {
-            return false;
+        if player == self.first || self.beats_winner(card, trump) {
+            self.set_winner(player, card, trump);
         }
 
-        if points::strength(card, trump)
-            > points::strength(self.cards[self.winner as usize].unwrap(), trump)
-        {
-            self.winner = player
+        player == self.first.prev()
+    }
+
+    /// Takes back the card played by `player`, if any.
+    ///
+    /// Updates the winner among the remaining cards.
+    pub fn take_back(
+        &mut self,
+        player: pos::PlayerPos,
+        trump: cards::Trump,
+    ) -> Option<cards::Card> {
+        let card = self.cards[player as usize].take()?;
+        self.recompute_winner(trump);
+        Some(card)
+    }
+
+    /// Recomputes the winner from the cards on the table.
+    ///
+    /// Use this on tricks built by hand or deserialized from an untrusted source.
+    pub fn recompute_winner(&mut self, trump: cards::Trump) {
+        self.winner = self.first;
+        self.strength = 0;
+        self.beating = cards::Hand::new();
+        for p in self.first.until_n(4) {
+            if let Some(card) = self.cards[p as usize] {
+                if p == self.first || self.beats_winner(card, trump) {
+                    self.set_winner(p, card, trump);
+                }
+            }
         }
+    }
 
-        player == self.first.prev()
+    /// Returns the card currently winning the trick, if any.
+    pub fn winning_card(&self) -> Option<cards::Card> {
+        self.cards[self.winner as usize]
+    }
+
+    // Same as `points::beats`, using the cached strength.
+    fn beats_winner(&self, card: cards::Card, trump: cards::Trump) -> bool {
+        let winner = match self.winning_card() {
+            Some(winner) => winner,
+            None => return true,
+        };
+        if card.suit() == winner.suit() {
+            points::strength(card, trump) > self.strength
+        } else {
+            trump.is_trump(card.suit()) && !trump.is_trump(winner.suit())
+        }
+    }
+
+    fn set_winner(&mut self, player: pos::PlayerPos, card: cards::Card, trump: cards::Trump) {
+        self.winner = player;
+        self.strength = points::strength(card, trump);
+        self.beating = beating_cards(card, trump);
+    }
+
+    /// Returns the cards that would beat the winning card, in the suit of that card.
+    ///
+    /// This is updated after each card played, so players can check
+    /// whether they must raise without looking at every card on the table.
+    pub fn beating(&self) -> cards::Hand {
+        self.beating
+    }
+
+    /// Returns the cards played in this trick, in the order they were played.
+    pub fn iter_in_play_order(&self) -> impl Iterator<Item = (pos::PlayerPos, cards::Card)> + '_ {
+        self.first
+            .until_n(4)
+            .filter_map(move |p| self.cards[p as usize].map(|card| (p, card)))
+    }
+
+    /// Returns the number of cards played in this trick.
+    pub fn len(&self) -> usize {
+        self.cards.iter().filter(|c| c.is_some()).count()
+    }
+
+    /// Returns `true` if no card was played in this trick yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if every player played a card in this trick.
+    pub fn is_complete(&self) -> bool {
+        self.len() == 4
+    }
+
+    /// Returns the cards played in this trick.
+    pub fn cards_played(&self) -> cards::Hand {
+        let mut hand = cards::Hand::new();
+        for (_, card) in self.iter_in_play_order() {
+            hand.add(card);
+        }
+        hand
+    }
+
+    /// Returns the players who haven't played in this trick yet, in play order.
+    pub fn remaining_players(&self) -> Vec<pos::PlayerPos> {
+        self.first
+            .until_n(4)
+            .filter(|&p| self.cards[p as usize].is_none())
+            .collect()
     }
 
     /// Returns the starting suit for this trick.
@@ -65,3 +183,273 @@ impl Trick {
         self.cards[self.first as usize].map(|c| c.suit())
     }
 }
+
+// Serialized form of a trick, checked before building the `Trick`.
+#[derive(serde::Deserialize)]
+struct TrickData {
+    cards: [Option<cards::Card>; 4],
+    first: pos::PlayerPos,
+    winner: pos::PlayerPos,
+    #[serde(default)]
+    strength: Option<i32>,
+    #[serde(default)]
+    beating: Option<cards::Hand>,
+}
+
+impl TryFrom<TrickData> for Trick {
+    type Error = String;
+
+    fn try_from(data: TrickData) -> Result<Self, String> {
+        let mut trick = Trick::new(data.first);
+        trick.cards = data.cards;
+
+        let mut seen = cards::Hand::new();
+        let mut ended = false;
+        for p in data.first.until_n(4) {
+            match data.cards[p as usize] {
+                Some(_) if ended => {
+                    return Err(format!("{} played before the previous players", p));
+                }
+                Some(card) if seen.has(card) => return Err(format!("{} appears twice", card)),
+                Some(card) => {
+                    seen.add(card);
+                }
+                None => ended = true,
+            }
+        }
+
+        let (lead, winner) = match (trick.suit(), data.cards[data.winner as usize]) {
+            (None, _) if data.winner == data.first => return Ok(trick),
+            (Some(lead), Some(winner)) => (lead, winner),
+            _ => return Err(format!("winner {} did not play", data.winner)),
+        };
+
+        // Without the trump, only tell whether the winning card is a trump.
+        // Older tricks lack the cached strength: accept either reading of the led suit.
+        let readings: &[bool] = match data.strength {
+            Some(s) => &[s >= 8][..],
+            None if winner.suit() != lead => &[true],
+            None => &[false, true],
+        };
+        let mut beaten_by = None;
+        for &is_trump in readings {
+            if !is_trump && winner.suit() != lead {
+                return Err(format!("{} cannot win a trick led with {}", winner, lead));
+            }
+            let trump = if is_trump {
+                cards::Trump::AllTrump
+            } else {
+                cards::Trump::NoTrump
+            };
+
+            trick.set_winner(data.winner, winner, trump);
+            beaten_by = trick
+                .iter_in_play_order()
+                .map(|(_, card)| card)
+                .find(|&card| card.suit() == winner.suit() && trick.beating.has(card));
+            if beaten_by.is_none() {
+                break;
+            }
+        }
+        if let Some(card) = beaten_by {
+            return Err(format!("{} is beaten by {}", winner, card));
+        }
+        if data.strength.is_some_and(|s| s != trick.strength)
+            || data.beating.is_some_and(|b| b != trick.beating)
+        {
+            return Err("cached strength does not match the winner".to_string());
+        }
+
+        Ok(trick)
+    }
+}
+
+// Returns the cards of the same suit that are stronger than `card`.
+fn beating_cards(card: cards::Card, trump: cards::Trump) -> cards::Hand {
+    let strength = points::strength(card, trump);
+    let mut result = cards::Hand::new();
+    for rank in (0..8).map(cards::Rank::from_n) {
+        let other = cards::Card::new(card.suit(), rank);
+        if points::strength(other, trump) > strength {
+            result.add(other);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cards, pos};
+
+    #[test]
+    fn test_winner() {
+        use cards::{Card, Rank, Suit};
+
+        let trump = cards::Trump::Suit(Suit::Club);
+        let mut trick = Trick::new(pos::PlayerPos::P1);
+        trick.play_card(
+            pos::PlayerPos::P1,
+            Card::new(Suit::Heart, Rank::RankX),
+            trump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P2,
+            Card::new(Suit::Heart, Rank::RankA),
+            trump,
+        );
+        assert_eq!(trick.winner, pos::PlayerPos::P2);
+        trick.play_card(
+            pos::PlayerPos::P3,
+            Card::new(Suit::Club, Rank::Rank7),
+            trump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P0,
+            Card::new(Suit::Heart, Rank::Rank9),
+            trump,
+        );
+        assert_eq!(trick.winner, pos::PlayerPos::P3);
+        assert!(trick.is_complete());
+        assert_eq!(trick.cards_played().size(), 4);
+        assert!(trick.remaining_players().is_empty());
+        let order: Vec<_> = trick.iter_in_play_order().map(|(p, _)| p).collect();
+        assert_eq!(
+            order,
+            vec![
+                pos::PlayerPos::P1,
+                pos::PlayerPos::P2,
+                pos::PlayerPos::P3,
+                pos::PlayerPos::P0
+            ]
+        );
+        assert_eq!(
+            trick.winning_card(),
+            Some(Card::new(Suit::Club, Rank::Rank7))
+        );
+        assert_eq!(trick.beating().size(), 7);
+
+        // Rebuild the caches.
+        let mut restored = trick;
+        restored.winner = pos::PlayerPos::P1;
+        restored.recompute_winner(trump);
+        assert_eq!(restored.winner, pos::PlayerPos::P3);
+        assert_eq!(restored.beating(), trick.beating());
+
+        assert_eq!(
+            trick.take_back(pos::PlayerPos::P3, trump),
+            Some(Card::new(Suit::Club, Rank::Rank7))
+        );
+        assert_eq!(trick.winner, pos::PlayerPos::P2);
+        assert!(trick.beating().is_empty());
+        assert!(!trick.is_complete());
+        assert!(!trick.cards_played().has(Card::new(Suit::Club, Rank::Rank7)));
+        assert_eq!(trick.remaining_players(), vec![pos::PlayerPos::P3]);
+        assert_eq!(
+            trick.iter_in_play_order().last(),
+            Some((pos::PlayerPos::P0, Card::new(Suit::Heart, Rank::Rank9)))
+        );
+    }
+
+    #[test]
+    fn test_beating() {
+        use cards::{Card, Rank, Suit, Trump};
+
+        // Cards of the winner's suit that beat it, found without the cache.
+        let expected = |trick: &Trick, trump: Trump| {
+            let mut hand = cards::Hand::new();
+            if let Some(winner) = trick.winning_card() {
+                for card in (0..32).map(Card::from_id) {
+                    if card.suit() == winner.suit() && points::beats(card, winner, trump) {
+                        hand.add(card);
+                    }
+                }
+            }
+            hand
+        };
+
+        let plays = [
+            (pos::PlayerPos::P0, Card::new(Suit::Spade, Rank::RankX)),
+            (pos::PlayerPos::P1, Card::new(Suit::Spade, Rank::Rank9)),
+            (pos::PlayerPos::P2, Card::new(Suit::Heart, Rank::Rank7)),
+            (pos::PlayerPos::P3, Card::new(Suit::Spade, Rank::RankA)),
+        ];
+        for &trump in &[
+            Trump::Suit(Suit::Heart),
+            Trump::Suit(Suit::Spade),
+            Trump::NoTrump,
+            Trump::AllTrump,
+        ] {
+            let mut trick = Trick::new(pos::PlayerPos::P0);
+            assert!(trick.beating().is_empty());
+            for &(p, card) in &plays {
+                trick.play_card(p, card, trump);
+                assert_eq!(trick.beating(), expected(&trick, trump), "{:?}", trump);
+            }
+            for &(p, _) in plays.iter().rev() {
+                trick.take_back(p, trump);
+                assert_eq!(trick.beating(), expected(&trick, trump), "{:?}", trump);
+            }
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        use cards::{Card, Rank, Suit};
+
+        let trump = cards::Trump::Suit(Suit::Spade);
+        let mut trick = Trick::new(pos::PlayerPos::P2);
+        trick.play_card(
+            pos::PlayerPos::P2,
+            Card::new(Suit::Spade, Rank::RankX),
+            trump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P3,
+            Card::new(Suit::Spade, Rank::Rank9),
+            trump,
+        );
+        trick.play_card(
+            pos::PlayerPos::P0,
+            Card::new(Suit::Heart, Rank::RankA),
+            trump,
+        );
+
+        let json = serde_json::to_string(&trick).unwrap();
+        let restored: Trick = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.winner, pos::PlayerPos::P3);
+        assert_eq!(restored.beating(), trick.beating());
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let forge = |value: &serde_json::Value| serde_json::from_value::<Trick>(value.clone());
+
+        // Older tricks without cached values: the led suit may or may not be trump.
+        let mut old = value.clone();
+        old.as_object_mut().unwrap().remove("strength");
+        old.as_object_mut().unwrap().remove("beating");
+        let mut restored = forge(&old).unwrap();
+        assert_eq!(restored.winner, pos::PlayerPos::P3);
+        restored.recompute_winner(trump);
+        assert_eq!(restored, trick);
+        old["winner"] = serde_json::json!("P2");
+        assert_eq!(forge(&old).unwrap().winner, pos::PlayerPos::P2);
+        old["winner"] = serde_json::json!("P1");
+        assert!(forge(&old).is_err());
+
+        // The winner must have played the best card.
+        value["winner"] = serde_json::json!("P0");
+        assert!(forge(&value).is_err());
+        value["winner"] = serde_json::json!("P1");
+        assert!(forge(&value).is_err());
+
+        // Cards must be played in order.
+        value["winner"] = serde_json::json!("P3");
+        value["first"] = serde_json::json!("P1");
+        assert!(forge(&value).is_err());
+
+        // No card twice.
+        value["first"] = serde_json::json!("P2");
+        value["cards"][0] = value["cards"][3].clone();
+        assert!(forge(&value).is_err());
+    }
+}